@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Duration;
+use clap::Subcommand;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::daemon::control::{
+    ControlRequest, ControlResponse, ReloadSettingsPatch, control_socket_path,
+};
+
+use super::timeline::format_duration;
+
+/// Runtime commands sent to an already-running daemon over its control socket, instead of
+/// stopping and re-spawning a detached process just to change its behavior.
+#[derive(Debug, Subcommand)]
+pub enum ControlCommand {
+    #[command(about = "Print the daemon's live status: uptime, active window, today's events")]
+    Status {},
+    #[command(about = "Force the current record file to flush to disk")]
+    Flush {},
+    #[command(about = "Temporarily stop recording without stopping the daemon")]
+    Pause {},
+    #[command(about = "Resume recording after a pause")]
+    Resume {},
+    #[command(about = "Ask the running daemon to re-read its configuration")]
+    Reload {
+        #[arg(long, help = "New collection interval in seconds, if changing it")]
+        collection_interval_secs: Option<u64>,
+        #[arg(long, help = "New AFK threshold in seconds, if changing it")]
+        afk_threshold_secs: Option<u32>,
+    },
+}
+
+pub async fn process_control_command(app_dir: &Path, command: ControlCommand) -> Result<()> {
+    let request = match command {
+        ControlCommand::Status {} => ControlRequest::Status,
+        ControlCommand::Flush {} => ControlRequest::Flush,
+        ControlCommand::Pause {} => ControlRequest::Pause,
+        ControlCommand::Resume {} => ControlRequest::Resume,
+        ControlCommand::Reload {
+            collection_interval_secs,
+            afk_threshold_secs,
+        } => ControlRequest::Reload {
+            new_settings: ReloadSettingsPatch {
+                collection_interval_secs,
+                afk_threshold_secs,
+            },
+        },
+    };
+
+    let response = send_request(app_dir, request).await?;
+    print_response(response);
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn send_request(app_dir: &Path, request: ControlRequest) -> Result<ControlResponse> {
+    let path = control_socket_path(app_dir);
+    let stream = tokio::net::UnixStream::connect(&path).await.with_context(|| {
+        format!("Failed to connect to control socket at {path:?}. Is the daemon running?")
+    })?;
+    exchange(stream, request).await
+}
+
+#[cfg(windows)]
+async fn send_request(app_dir: &Path, request: ControlRequest) -> Result<ControlResponse> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+
+    let pipe_name = crate::daemon::control::control_pipe_name(app_dir);
+    let stream = ClientOptions::new().open(&pipe_name).with_context(|| {
+        format!("Failed to connect to control pipe {pipe_name}. Is the daemon running?")
+    })?;
+    exchange(stream, request).await
+}
+
+async fn exchange<S>(stream: S, request: ControlRequest) -> Result<ControlResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut payload = serde_json::to_vec(&request)?;
+    payload.push(b'\n');
+    writer
+        .write_all(&payload)
+        .await
+        .context("Failed to send control request")?;
+
+    let mut line = String::new();
+    BufReader::new(reader)
+        .read_line(&mut line)
+        .await
+        .context("Failed to read control response")?;
+    serde_json::from_str(&line).context("Failed to parse control response")
+}
+
+fn print_response(response: ControlResponse) {
+    match response {
+        ControlResponse::Status {
+            uptime_secs,
+            paused,
+            active_window,
+            events_today,
+        } => {
+            println!("Uptime:        {}", format_duration(Duration::seconds(uptime_secs)));
+            println!("Paused:        {paused}");
+            println!(
+                "Active window: {}",
+                active_window.as_deref().unwrap_or("unknown")
+            );
+            println!("Records today: {events_today}");
+        }
+        ControlResponse::Ok => println!("Ok"),
+        ControlResponse::Error(e) => eprintln!("Daemon reported an error: {e}"),
+    }
+}
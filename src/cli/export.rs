@@ -0,0 +1,574 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use arrow::{
+    array::{ArrayRef, BooleanArray, Int64Array, StringArray},
+    datatypes::{DataType, Field, Schema},
+    record_batch::RecordBatch,
+};
+use chrono::Local;
+use clap::{Parser, Subcommand};
+use futures::{Stream, StreamExt, TryStreamExt};
+use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::{AsyncWriteExt, BufWriter},
+};
+
+use crate::{
+    daemon::storage::{entities::UsageIntervalEntity, record_storage::RecordStorageImpl},
+    utils::csv,
+};
+
+use super::{
+    create_application_default_path,
+    output::{self, extract_between},
+    timeline::{DateRangeArgs, parse_date_range},
+};
+
+/// How many recent days to export when `--start` isn't given. Unlike `timeline` there's no
+/// interval to derive a sensible default from, so we pick a plain calendar window.
+const DEFAULT_EXPORT_DAYS: i64 = 7;
+
+/// Batch size for InfluxDB line-protocol writes. Keeps request bodies reasonably sized for a
+/// multi-month export without needing one POST per interval.
+const INFLUX_BATCH_SIZE: usize = 5000;
+
+/// How many intervals accumulate into a Parquet row group before being flushed, keeping a
+/// multi-month export from needing the whole file's data resident in memory at once.
+const PARQUET_BATCH_SIZE: usize = 4096;
+
+#[derive(Debug, Parser)]
+pub struct ExportCommand {
+    #[command(subcommand)]
+    sink: ExportSink,
+}
+
+#[derive(Debug, Subcommand)]
+enum ExportSink {
+    #[command(about = "Stream recorded intervals into InfluxDB as line-protocol points")]
+    Influx {
+        #[command(flatten)]
+        range: DateRangeArgs,
+        #[arg(long, default_value = "http://localhost:8086", help = "InfluxDB base URL")]
+        url: String,
+        #[arg(long, help = "InfluxDB database to write into")]
+        db: String,
+        #[arg(long, help = "Optional auth token sent as `Authorization: Token <token>`")]
+        token: Option<String>,
+    },
+    #[command(about = "Stream recorded intervals into a file as newline-delimited JSON, one object per interval")]
+    Json {
+        #[command(flatten)]
+        range: DateRangeArgs,
+        #[arg(long, help = "File to write NDJSON records into")]
+        path: PathBuf,
+    },
+    #[command(about = "Stream recorded intervals into a file as CSV")]
+    Csv {
+        #[command(flatten)]
+        range: DateRangeArgs,
+        #[arg(long, help = "File to write CSV rows into")]
+        path: PathBuf,
+    },
+    #[command(about = "Stream recorded intervals into a Parquet file, row-grouped as they arrive")]
+    Parquet {
+        #[command(flatten)]
+        range: DateRangeArgs,
+        #[arg(long, help = "File to write the Parquet file into")]
+        path: PathBuf,
+    },
+}
+
+pub async fn process_export_command(command: ExportCommand) -> Result<()> {
+    match command.sink {
+        ExportSink::Influx {
+            range,
+            url,
+            db,
+            token,
+        } => export_influx(range, url, db, token).await,
+        ExportSink::Json { range, path } => export_ndjson(range, path).await,
+        ExportSink::Csv { range, path } => export_csv(range, path).await,
+        ExportSink::Parquet { range, path } => export_parquet(range, path).await,
+    }
+}
+
+async fn export_influx(range: DateRangeArgs, url: String, db: String, token: Option<String>) -> Result<()> {
+    let treat_as_days = range.treat_as_days;
+    let default_start = Local::now() - chrono::Duration::days(DEFAULT_EXPORT_DAYS);
+    let (start, end) = parse_date_range(range, treat_as_days, default_start)?;
+
+    let application = RecordStorageImpl::new(create_application_default_path()?.join("records"))?;
+    let intervals = extract_between(
+        application,
+        output::ExtractConfig {
+            start: start.into(),
+            end: end.into(),
+            ..Default::default()
+        },
+    );
+
+    let client = reqwest::Client::new();
+    let write_url = format!("{}/write?db={}", url.trim_end_matches('/'), db);
+
+    let written = write_in_batches(intervals, |batch| {
+        let client = client.clone();
+        let write_url = write_url.clone();
+        let token = token.clone();
+        async move { post_batch(&client, &write_url, token.as_deref(), batch).await }
+    })
+    .await?;
+
+    println!("Exported {written} intervals to {url}");
+    Ok(())
+}
+
+/// Pulls from `intervals`, groups them into batches of [INFLUX_BATCH_SIZE] lines and hands each
+/// batch to `send`. Returns the total number of intervals written.
+async fn write_in_batches<F, Fut>(
+    intervals: impl Stream<Item = Result<UsageIntervalEntity>>,
+    mut send: F,
+) -> Result<usize>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let mut intervals = std::pin::pin!(intervals);
+    let mut batch = String::new();
+    let mut batch_len = 0;
+    let mut total = 0;
+
+    while let Some(interval) = intervals.try_next().await? {
+        batch.push_str(&to_line_protocol(&interval));
+        batch.push('\n');
+        batch_len += 1;
+        total += 1;
+
+        if batch_len >= INFLUX_BATCH_SIZE {
+            send(std::mem::take(&mut batch)).await?;
+            batch_len = 0;
+        }
+    }
+
+    if !batch.is_empty() {
+        send(batch).await?;
+    }
+
+    Ok(total)
+}
+
+async fn post_batch(client: &reqwest::Client, url: &str, token: Option<&str>, batch: String) -> Result<()> {
+    let mut request = client.post(url).body(batch);
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Token {token}"));
+    }
+    let response = request.send().await.context("Failed to reach InfluxDB")?;
+    response
+        .error_for_status()
+        .context("InfluxDB rejected the write")?;
+    Ok(())
+}
+
+/// Encodes a single interval as an InfluxDB line-protocol point, e.g.
+/// `whatawhat,process=firefox.exe,afk=false duration=135i,window="GitHub" 1710590400000000000`.
+fn to_line_protocol(interval: &UsageIntervalEntity) -> String {
+    format!(
+        "whatawhat,process={},afk={} duration={}i,window={} {}",
+        escape_tag_value(&interval.process_name),
+        interval.afk,
+        interval.duration.num_seconds(),
+        quote_field_value(&interval.window_name),
+        interval.start.timestamp_nanos_opt().unwrap_or_default(),
+    )
+}
+
+/// Tag values can't contain unescaped commas, spaces or equals signs.
+fn escape_tag_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(',', "\\,").replace(' ', "\\ ").replace('=', "\\=")
+}
+
+/// String field values must be double-quoted, with inner quotes and backslashes escaped. A literal
+/// newline or carriage return would otherwise split one point across multiple line-protocol lines,
+/// so those are escaped too, the same as the CSV sinks now do for embedded newlines.
+fn quote_field_value(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    )
+}
+
+/// Accepts [UsageIntervalEntity]s one at a time and writes them out incrementally, so exporting a
+/// multi-month range stays memory-bounded instead of collecting everything into a `Vec` first.
+/// Named `IntervalSink` rather than `ExportSink` to avoid colliding with the subcommand enum above.
+trait IntervalSink {
+    fn write_interval(&mut self, interval: &UsageIntervalEntity) -> impl std::future::Future<Output = Result<()>>;
+
+    fn finalize(&mut self) -> impl std::future::Future<Output = Result<()>>;
+}
+
+/// Shared row shape for the JSON/CSV/Parquet sinks. Timestamps are RFC 3339 and durations are
+/// whole seconds, rather than the Unix-seconds/custom encoding [UsageIntervalEntity] itself uses
+/// for disk storage, so the export round-trips through off-the-shelf analytics tooling and the
+/// human-friendly/RFC 3339 parsers this crate already has.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportRow {
+    start: String,
+    end: String,
+    duration_seconds: i64,
+    process_name: String,
+    window_name: String,
+    afk: bool,
+}
+
+impl From<&UsageIntervalEntity> for ExportRow {
+    fn from(interval: &UsageIntervalEntity) -> Self {
+        ExportRow {
+            start: interval.start.to_rfc3339(),
+            end: interval.end().to_rfc3339(),
+            duration_seconds: interval.duration.num_seconds(),
+            process_name: interval.process_name.to_string(),
+            window_name: interval.window_name.to_string(),
+            afk: interval.afk,
+        }
+    }
+}
+
+/// Shared tail end of the JSON/CSV/Parquet sinks: resolves the range, opens the stream and drains
+/// it into `sink`.
+async fn export_with_sink(range: DateRangeArgs, sink: impl IntervalSink, label: &str, path: &Path) -> Result<()> {
+    let treat_as_days = range.treat_as_days;
+    let default_start = Local::now() - chrono::Duration::days(DEFAULT_EXPORT_DAYS);
+    let (start, end) = parse_date_range(range, treat_as_days, default_start)?;
+
+    let application = RecordStorageImpl::new(create_application_default_path()?.join("records"))?;
+    let intervals = extract_between(
+        application,
+        output::ExtractConfig {
+            start: start.into(),
+            end: end.into(),
+            ..Default::default()
+        },
+    );
+
+    let written = drain_into_sink(intervals, sink).await?;
+    println!("Exported {written} intervals as {label} to {}", path.display());
+    Ok(())
+}
+
+/// Pulls from `intervals` and writes each one into `sink` as it arrives. Mirrors
+/// [write_in_batches]'s streaming shape, just without the Influx sink's request-batching.
+async fn drain_into_sink(
+    intervals: impl Stream<Item = Result<UsageIntervalEntity>>,
+    mut sink: impl IntervalSink,
+) -> Result<usize> {
+    let mut intervals = std::pin::pin!(intervals);
+    let mut total = 0;
+
+    while let Some(interval) = intervals.try_next().await? {
+        sink.write_interval(&interval).await?;
+        total += 1;
+    }
+
+    sink.finalize().await?;
+    Ok(total)
+}
+
+async fn export_ndjson(range: DateRangeArgs, path: PathBuf) -> Result<()> {
+    let sink = NdjsonSink::create(&path).await?;
+    export_with_sink(range, sink, "NDJSON", &path).await
+}
+
+struct NdjsonSink {
+    writer: BufWriter<File>,
+}
+
+impl NdjsonSink {
+    async fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .await
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+impl IntervalSink for NdjsonSink {
+    async fn write_interval(&mut self, interval: &UsageIntervalEntity) -> Result<()> {
+        let line = serde_json::to_string(&ExportRow::from(interval))?;
+        self.writer.write_all(line.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        Ok(())
+    }
+
+    async fn finalize(&mut self) -> Result<()> {
+        self.writer.flush().await.context("Failed to flush NDJSON export")
+    }
+}
+
+async fn export_csv(range: DateRangeArgs, path: PathBuf) -> Result<()> {
+    let sink = CsvSink::create(&path).await?;
+    export_with_sink(range, sink, "CSV", &path).await
+}
+
+struct CsvSink {
+    writer: BufWriter<File>,
+    header_written: bool,
+}
+
+impl CsvSink {
+    async fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .await
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            header_written: false,
+        })
+    }
+}
+
+impl IntervalSink for CsvSink {
+    async fn write_interval(&mut self, interval: &UsageIntervalEntity) -> Result<()> {
+        if !self.header_written {
+            self.writer
+                .write_all(b"start,end,duration_seconds,process_name,window_name,afk\n")
+                .await?;
+            self.header_written = true;
+        }
+
+        let row = ExportRow::from(interval);
+        let line = format!(
+            "{},{},{},{},{},{}\n",
+            row.start,
+            row.end,
+            row.duration_seconds,
+            csv::escape(&row.process_name),
+            csv::escape(&row.window_name),
+            row.afk,
+        );
+        self.writer.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    async fn finalize(&mut self) -> Result<()> {
+        self.writer.flush().await.context("Failed to flush CSV export")
+    }
+}
+
+async fn export_parquet(range: DateRangeArgs, path: PathBuf) -> Result<()> {
+    let sink = ParquetSink::create(&path)?;
+    export_with_sink(range, sink, "Parquet", &path).await
+}
+
+/// Parquet has no async writer, so unlike the other two sinks this one writes its row groups
+/// synchronously; that's confined to `flush_batch`, called at most once per [PARQUET_BATCH_SIZE]
+/// intervals rather than per-interval, and to the writer's final `close` in `finalize`.
+struct ParquetSink {
+    writer: Option<ArrowWriter<std::fs::File>>,
+    schema: Arc<Schema>,
+    pending: Vec<ExportRow>,
+}
+
+impl ParquetSink {
+    fn create(path: &Path) -> Result<Self> {
+        let file = std::fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("start", DataType::Utf8, false),
+            Field::new("end", DataType::Utf8, false),
+            Field::new("duration_seconds", DataType::Int64, false),
+            Field::new("process_name", DataType::Utf8, false),
+            Field::new("window_name", DataType::Utf8, false),
+            Field::new("afk", DataType::Boolean, false),
+        ]));
+
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(WriterProperties::builder().build()))
+            .context("Failed to open Parquet writer")?;
+
+        Ok(Self {
+            writer: Some(writer),
+            schema,
+            pending: Vec::with_capacity(PARQUET_BATCH_SIZE),
+        })
+    }
+
+    fn flush_batch(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let rows = std::mem::take(&mut self.pending);
+        let start: ArrayRef = Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.start.as_str())));
+        let end: ArrayRef = Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.end.as_str())));
+        let duration_seconds: ArrayRef =
+            Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.duration_seconds)));
+        let process_name: ArrayRef =
+            Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.process_name.as_str())));
+        let window_name: ArrayRef =
+            Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.window_name.as_str())));
+        let afk: ArrayRef = Arc::new(BooleanArray::from_iter(rows.iter().map(|r| Some(r.afk))));
+
+        let batch = RecordBatch::try_new(
+            self.schema.clone(),
+            vec![start, end, duration_seconds, process_name, window_name, afk],
+        )
+        .context("Failed to build Parquet row group")?;
+
+        self.writer
+            .as_mut()
+            .expect("ParquetSink used after finalize")
+            .write(&batch)
+            .context("Failed to write Parquet row group")?;
+        Ok(())
+    }
+}
+
+impl IntervalSink for ParquetSink {
+    async fn write_interval(&mut self, interval: &UsageIntervalEntity) -> Result<()> {
+        self.pending.push(ExportRow::from(interval));
+        if self.pending.len() >= PARQUET_BATCH_SIZE {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    async fn finalize(&mut self) -> Result<()> {
+        self.flush_batch()?;
+        if let Some(writer) = self.writer.take() {
+            writer.close().context("Failed to finalize Parquet file")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration as ChronoDuration, TimeZone, Utc};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn sample_interval(process_name: &str, window_name: &str) -> UsageIntervalEntity {
+        UsageIntervalEntity {
+            window_name: window_name.into(),
+            process_name: process_name.into(),
+            start: Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap(),
+            duration: ChronoDuration::seconds(90),
+            afk: false,
+        }
+    }
+
+    #[test]
+    fn escape_tag_value_escapes_commas_spaces_equals_and_backslashes() {
+        assert_eq!(escape_tag_value("firefox"), "firefox");
+        assert_eq!(escape_tag_value("a,b"), r"a\,b");
+        assert_eq!(escape_tag_value("a b"), r"a\ b");
+        assert_eq!(escape_tag_value("a=b"), r"a\=b");
+        assert_eq!(escape_tag_value(r"a\b"), r"a\\b");
+    }
+
+    #[test]
+    fn quote_field_value_escapes_quotes_and_backslashes() {
+        assert_eq!(quote_field_value("GitHub"), "\"GitHub\"");
+        assert_eq!(quote_field_value(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote_field_value(r"a\b"), r#""a\\b""#);
+    }
+
+    #[test]
+    fn quote_field_value_escapes_embedded_newlines_and_carriage_returns() {
+        assert_eq!(quote_field_value("line1\nline2"), r#""line1\nline2""#);
+        assert_eq!(quote_field_value("line1\r\nline2"), r#""line1\r\nline2""#);
+    }
+
+    #[test]
+    fn to_line_protocol_encodes_process_duration_window_and_timestamp() {
+        let interval = sample_interval("firefox", "GitHub");
+        let line = to_line_protocol(&interval);
+        assert_eq!(
+            line,
+            format!(
+                "whatawhat,process=firefox,afk=false duration=90i,window=\"GitHub\" {}",
+                interval.start.timestamp_nanos_opt().unwrap()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn ndjson_sink_round_trips_rows() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("export.ndjson");
+
+        let mut sink = NdjsonSink::create(&path).await?;
+        sink.write_interval(&sample_interval("firefox", "GitHub")).await?;
+        sink.write_interval(&sample_interval("vim", "main.rs")).await?;
+        sink.finalize().await?;
+
+        let contents = tokio::fs::read_to_string(&path).await?;
+        let rows: Vec<ExportRow> = contents.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].process_name, "firefox");
+        assert_eq!(rows[0].window_name, "GitHub");
+        assert_eq!(rows[0].duration_seconds, 90);
+        assert_eq!(rows[1].process_name, "vim");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn csv_sink_round_trips_rows_and_escapes_commas() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("export.csv");
+
+        let mut sink = CsvSink::create(&path).await?;
+        sink.write_interval(&sample_interval("firefox", "a,b")).await?;
+        sink.finalize().await?;
+
+        let contents = tokio::fs::read_to_string(&path).await?;
+        let mut lines = contents.lines();
+        assert_eq!(lines.next(), Some("start,end,duration_seconds,process_name,window_name,afk"));
+        assert_eq!(lines.next(), Some("2024-01-01T12:00:00+00:00,2024-01-01T12:01:30+00:00,90,firefox,\"a,b\",false"));
+        assert_eq!(lines.next(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn parquet_sink_round_trips_rows() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("export.parquet");
+
+        let mut sink = ParquetSink::create(&path)?;
+        sink.write_interval(&sample_interval("firefox", "GitHub")).await?;
+        sink.write_interval(&sample_interval("vim", "main.rs")).await?;
+        sink.finalize().await?;
+
+        let file = std::fs::File::open(&path)?;
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+        let batches: Vec<_> = reader.collect::<std::result::Result<_, _>>()?;
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 2);
+
+        let first = &batches[0];
+        let process_name = first
+            .column_by_name("process_name")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(process_name.value(0), "firefox");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use tokio::{fs::File, io::AsyncSeekExt};
+
+use crate::{
+    fs::operations::{follow_file_once, seek_last_lines},
+    utils::logging::DAEMON_PREFIX,
+};
+
+use super::create_application_default_path;
+
+/// How often `--follow` polls the log file for new bytes. Polling is used instead of
+/// inotify/kqueue so a single tailed file doesn't need a platform-specific watch dependency.
+const POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Prints the daemon's most recent log lines, optionally streaming new ones as they're written.
+#[derive(Debug, Parser)]
+pub struct LogsCommand {
+    #[arg(short, long, help = "Keep streaming newly appended lines after printing the tail")]
+    follow: bool,
+    #[arg(short = 'n', long, default_value_t = 20, help = "Number of trailing lines to print")]
+    lines: usize,
+}
+
+pub async fn process_logs_command(command: LogsCommand) -> Result<()> {
+    let logs_dir = create_application_default_path()?.join("logs");
+    let mut path = newest_log_file(&logs_dir)
+        .with_context(|| format!("No daemon log files found in {logs_dir:?}"))?;
+
+    let mut file = File::open(&path).await?;
+    let mut seek_buffer = vec![0u8; 4096];
+    file.seek(std::io::SeekFrom::End(0)).await?;
+    seek_last_lines(&mut file, command.lines, &mut seek_buffer).await?;
+
+    let mut position = file.stream_position().await?;
+    position = follow_file_once(&mut file, position, tokio::io::stdout()).await?;
+
+    if !command.follow {
+        return Ok(());
+    }
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        position = follow_file_once(&mut file, position, tokio::io::stdout()).await?;
+
+        if let Some(newest) = newest_log_file(&logs_dir) {
+            if newest != path {
+                file = File::open(&newest).await?;
+                position = 0;
+                path = newest;
+            }
+        }
+    }
+}
+
+/// Picks the most recently modified file whose name starts with [DAEMON_PREFIX] in `logs_dir`,
+/// mirroring how `tracing_appender`'s hourly rotation names its files.
+fn newest_log_file(logs_dir: &std::path::Path) -> Option<PathBuf> {
+    std::fs::read_dir(logs_dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with(DAEMON_PREFIX))
+        })
+        .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+        .map(|entry| entry.path())
+}
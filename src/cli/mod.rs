@@ -1,15 +1,30 @@
+pub mod control;
 pub mod daemon_path;
+pub mod export;
+pub mod logs;
 pub mod output;
 pub mod process;
+pub mod service;
+#[cfg(feature = "http")]
+pub mod serve;
+pub mod status;
 pub mod timeline;
 
-use std::{env, ffi::OsString, path::PathBuf};
+use std::{ffi::OsString, path::{Path, PathBuf}};
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use daemon_path::to_daemon_path;
-use process::{kill_previous_daemons, restart_daemon};
-use timeline::{TimelineCommand, process_timeline_command};
+use control::{ControlCommand, process_control_command};
+use export::{ExportCommand, process_export_command};
+use logs::{LogsCommand, process_logs_command};
+use process::{restart_daemon, stop_daemon_via_pid_file};
+#[cfg(feature = "http")]
+use serve::{ServeCommand, process_serve_command};
+use service::{ServiceCommand, process_service_command};
+use status::process_status_command;
+use timeline::{
+    Period, PeriodCommand, TimelineCommand, process_period_command, process_timeline_command,
+};
 use tracing::level_filters::LevelFilter;
 
 use crate::utils::{
@@ -45,6 +60,54 @@ enum Commands {
     },
     #[command(about = "Stop currently running daemon.")]
     Stop {},
+    #[command(about = "Print the running daemon's health, e.g. uptime and events processed")]
+    Status {},
+    #[command(about = "Send a runtime command to an already-running daemon's control socket")]
+    Control {
+        #[command(subcommand)]
+        command: ControlCommand,
+    },
+    #[command(about = "Manage whatawhat as a native OS service (systemd/launchd/Windows service)")]
+    Service {
+        #[command(subcommand)]
+        command: ServiceCommand,
+    },
+    #[command(about = "Print (and optionally follow) the daemon's log output")]
+    Logs {
+        #[command(flatten)]
+        command: LogsCommand,
+    },
+    #[command(about = "Export recorded activity to an external sink")]
+    Export {
+        #[command(flatten)]
+        command: ExportCommand,
+    },
+    #[command(about = "Display a timeline of user activity for today (or another day with an offset)")]
+    Day {
+        #[command(flatten)]
+        command: PeriodCommand,
+    },
+    #[command(about = "Display a timeline of user activity for this week (or another week with an offset)")]
+    Week {
+        #[command(flatten)]
+        command: PeriodCommand,
+    },
+    #[command(about = "Display a timeline of user activity for this month (or another month with an offset)")]
+    Month {
+        #[command(flatten)]
+        command: PeriodCommand,
+    },
+    #[command(about = "Display a timeline of user activity for this year (or another year with an offset)")]
+    Year {
+        #[command(flatten)]
+        command: PeriodCommand,
+    },
+    #[cfg(feature = "http")]
+    #[command(about = "Serve usage data over a local HTTP query API")]
+    Serve {
+        #[command(flatten)]
+        command: ServeCommand,
+    },
 }
 
 pub fn run_cli(values: impl Iterator<Item = OsString>) -> Result<()> {
@@ -73,7 +136,15 @@ pub fn run_cli(values: impl Iterator<Item = OsString>) -> Result<()> {
             Ok(())
         }
         Commands::Stop {} => {
-            stop_daemon();
+            stop_daemon(&app_dir);
+            Ok(())
+        }
+        Commands::Status {} => process_status_command(),
+        Commands::Control { command } => multi_thread_runtime()?
+            .block_on(async move { process_control_command(&app_dir, command).await }),
+        Commands::Service { command } => process_service_command(command),
+        Commands::Logs { command } => {
+            multi_thread_runtime()?.block_on(async move { process_logs_command(command).await })?;
             Ok(())
         }
         Commands::Timeline { command } => {
@@ -81,19 +152,46 @@ pub fn run_cli(values: impl Iterator<Item = OsString>) -> Result<()> {
                 .block_on(async move { process_timeline_command(command).await })?;
             Ok(())
         }
+        Commands::Export { command } => {
+            multi_thread_runtime()?
+                .block_on(async move { process_export_command(command).await })?;
+            Ok(())
+        }
+        Commands::Day { command } => {
+            multi_thread_runtime()?
+                .block_on(async move { process_period_command(Period::Day, command).await })?;
+            Ok(())
+        }
+        Commands::Week { command } => {
+            multi_thread_runtime()?
+                .block_on(async move { process_period_command(Period::Week, command).await })?;
+            Ok(())
+        }
+        Commands::Month { command } => {
+            multi_thread_runtime()?
+                .block_on(async move { process_period_command(Period::Month, command).await })?;
+            Ok(())
+        }
+        Commands::Year { command } => {
+            multi_thread_runtime()?
+                .block_on(async move { process_period_command(Period::Year, command).await })?;
+            Ok(())
+        }
+        #[cfg(feature = "http")]
+        Commands::Serve { command } => {
+            multi_thread_runtime()?.block_on(async move { process_serve_command(command).await })?;
+            Ok(())
+        }
     }
 }
 
-fn stop_daemon() {
-    let process_name =
-        to_daemon_path(env::current_exe().expect("Failed to get current executable"));
-    println!("Inferred daemon name {process_name:?}");
-    match kill_previous_daemons(&process_name) {
+fn stop_daemon(app_dir: &Path) {
+    match stop_daemon_via_pid_file(app_dir) {
         Ok(_) => {
-            println!("Previous daemons killed")
+            println!("Daemon stopped")
         },
         Err(e) => {
-            eprintln!("Failed killing daemons {e}")
+            eprintln!("Failed stopping daemon {e}")
         },
     };
 }
@@ -0,0 +1,192 @@
+use std::fmt::Display;
+
+use chrono::Duration;
+use clap::ValueEnum;
+use terminal_size::{Width, terminal_size};
+
+use crate::{
+    cli::{output::analysis::ProcessUsage, timeline::clean_process_name},
+    utils::percentage::{Percentage, duration_percentage},
+};
+
+/// Fallback width used when stdout isn't a terminal (e.g. piped to a file) and `--chart-width`
+/// wasn't given.
+const DEFAULT_WIDTH: u16 = 80;
+
+/// How `--output chart` renders the grouped timeline. `Bar` prints one labeled stacked bar per
+/// bucket; `Sparkline` compresses the whole timeline into a single line, one glyph per bucket, for
+/// a quick at-a-glance overview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ChartStyle {
+    Bar,
+    Sparkline,
+}
+
+impl Display for ChartStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChartStyle::Bar => write!(f, "bar"),
+            ChartStyle::Sparkline => write!(f, "sparkline"),
+        }
+    }
+}
+
+/// One segment of a bucket's stacked bar: a process (or the folded "Other" remainder) and its
+/// share of that bucket's tracked duration.
+struct ChartSegment {
+    label: String,
+    percentage: Percentage,
+}
+
+/// Keeps the `top_n` largest entries of an already duration-sorted-descending [ProcessUsage] list
+/// and folds everything past that into a single "Other" segment, so a bucket with dozens of
+/// processes still renders as a handful of bar segments.
+fn fold_top_n(usages: Vec<ProcessUsage>, top_n: usize, computer_on_duration: Duration) -> Vec<ChartSegment> {
+    let mut segments = Vec::new();
+    let mut other_duration = Duration::zero();
+
+    for (i, usage) in usages.into_iter().enumerate() {
+        if i < top_n {
+            segments.push(ChartSegment {
+                label: clean_process_name(&usage.process_name),
+                percentage: duration_percentage(usage.duration, computer_on_duration),
+            });
+        } else {
+            other_duration += usage.duration;
+        }
+    }
+
+    if !other_duration.is_zero() {
+        segments.push(ChartSegment {
+            label: "Other".to_string(),
+            percentage: duration_percentage(other_duration, computer_on_duration),
+        });
+    }
+
+    segments
+}
+
+/// A stable, visually-distinct ANSI 256-color index for `label`, so the same process gets the same
+/// color in every bucket's bar without keeping a palette-assignment table around.
+fn color_for(label: &str) -> u8 {
+    // A curated set of readable foreground colors from the 256-color palette, skipping the very
+    // dark and near-white entries that are hard to read on common terminal backgrounds.
+    const PALETTE: [u8; 12] = [32, 33, 38, 64, 94, 95, 130, 136, 160, 166, 172, 178];
+
+    let hash = label.bytes().fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
+    PALETTE[hash as usize % PALETTE.len()]
+}
+
+fn resolve_width(width: Option<u16>) -> u16 {
+    width
+        .or_else(|| terminal_size().map(|(Width(w), _)| w))
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Renders one full-width stacked bar per `(time, segments)` row, with a `label 12%` legend after
+/// each segment's block run.
+fn render_bar(rows: &[(String, Vec<ChartSegment>)], width: u16) {
+    let label_width = rows.iter().map(|(time, _)| time.len()).max().unwrap_or(0);
+    let bar_width = width.saturating_sub(label_width as u16 + 1).max(1) as usize;
+
+    for (time, segments) in rows {
+        print!("{:>label_width$} ", time, label_width = label_width);
+
+        let mut legend = String::new();
+        for segment in segments {
+            let cells = ((*segment.percentage / 100.) * bar_width as f64).round() as usize;
+            if cells == 0 {
+                continue;
+            }
+            let color = color_for(&segment.label);
+            print!("\x1b[48;5;{color}m{}\x1b[0m", " ".repeat(cells));
+            if !legend.is_empty() {
+                legend.push_str(", ");
+            }
+            legend.push_str(&format!("{} {}%", segment.label, segment.percentage));
+        }
+        println!(" {legend}");
+    }
+}
+
+/// Renders the whole timeline as a single line, one `▁`-`█` glyph per bucket, scaled by that
+/// bucket's busiest segment's share. This is a coarse overview; use `Bar` for a per-process
+/// breakdown.
+fn render_sparkline(rows: &[(String, Vec<ChartSegment>)]) {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let line: String = rows
+        .iter()
+        .map(|(_, segments)| {
+            let busiest = segments.iter().map(|s| *s.percentage).fold(0., f64::max);
+            let level = ((busiest / 100.) * (LEVELS.len() - 1) as f64).round() as usize;
+            LEVELS[level.min(LEVELS.len() - 1)]
+        })
+        .collect();
+
+    println!("{line}");
+}
+
+/// Renders a grouped process timeline (one `(time label, per-process usage, bucket total)` triple
+/// per bucket) as a terminal chart. `top_n` folds anything past the top N processes per bucket into
+/// an "Other" segment; `width` overrides auto-detection of the terminal's column count.
+pub fn render_process_timeline(
+    style: ChartStyle,
+    top_n: usize,
+    width: Option<u16>,
+    rows: Vec<(String, Vec<ProcessUsage>, Duration)>,
+) {
+    let rows: Vec<(String, Vec<ChartSegment>)> = rows
+        .into_iter()
+        .map(|(time, usages, computer_on_duration)| (time, fold_top_n(usages, top_n, computer_on_duration)))
+        .collect();
+
+    match style {
+        ChartStyle::Bar => render_bar(&rows, resolve_width(width)),
+        ChartStyle::Sparkline => render_sparkline(&rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(process_name: &str, duration_secs: i64) -> ProcessUsage {
+        ProcessUsage {
+            process_name: process_name.into(),
+            duration: Duration::seconds(duration_secs),
+        }
+    }
+
+    #[test]
+    fn fold_top_n_keeps_the_largest_entries_and_folds_the_rest_into_other() {
+        let usages = vec![usage("firefox", 60), usage("alacritty", 30), usage("slack", 10)];
+        let segments = fold_top_n(usages, 2, Duration::seconds(100));
+
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].label, "firefox");
+        assert_eq!(segments[1].label, "alacritty");
+        assert_eq!(segments[2].label, "Other");
+        assert_eq!(*segments[2].percentage, 10.);
+    }
+
+    #[test]
+    fn fold_top_n_omits_other_when_everything_fits() {
+        let usages = vec![usage("firefox", 60), usage("alacritty", 30)];
+        let segments = fold_top_n(usages, 5, Duration::seconds(90));
+
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|s| s.label != "Other"));
+    }
+
+    #[test]
+    fn color_for_is_stable_and_distinguishes_labels() {
+        assert_eq!(color_for("firefox"), color_for("firefox"));
+        assert_ne!(color_for("firefox"), color_for("alacritty"));
+    }
+
+    #[test]
+    fn resolve_width_prefers_the_explicit_override() {
+        assert_eq!(resolve_width(Some(42)), 42);
+    }
+}
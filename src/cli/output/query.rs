@@ -0,0 +1,337 @@
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Duration;
+
+use crate::daemon::storage::entities::UsageIntervalEntity;
+
+/// A boolean filter expression matched against every [UsageIntervalEntity] before it reaches
+/// `analyze_processes`/`analyze_windows`, e.g. `process:firefox and not afk:true`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryExpr {
+    Process(String),
+    Window(String),
+    Afk(bool),
+    Duration(Comparator, Duration),
+    And(Box<QueryExpr>, Box<QueryExpr>),
+    Or(Box<QueryExpr>, Box<QueryExpr>),
+    Not(Box<QueryExpr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparator {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+}
+
+impl QueryExpr {
+    pub fn matches(&self, entity: &UsageIntervalEntity) -> bool {
+        match self {
+            QueryExpr::Process(needle) => contains_ignore_case(&entity.process_name, needle),
+            QueryExpr::Window(needle) => contains_ignore_case(&entity.window_name, needle),
+            QueryExpr::Afk(expected) => entity.afk == *expected,
+            QueryExpr::Duration(comparator, value) => {
+                let actual = entity.duration;
+                match comparator {
+                    Comparator::Greater => actual > *value,
+                    Comparator::GreaterOrEqual => actual >= *value,
+                    Comparator::Less => actual < *value,
+                    Comparator::LessOrEqual => actual <= *value,
+                    Comparator::Equal => actual == *value,
+                }
+            }
+            QueryExpr::And(a, b) => a.matches(entity) && b.matches(entity),
+            QueryExpr::Or(a, b) => a.matches(entity) || b.matches(entity),
+            QueryExpr::Not(inner) => !inner.matches(entity),
+        }
+    }
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// Parses a query expression like `process:firefox and not (afk:true or duration<30s)`.
+///
+/// Grammar (lowest to highest precedence): `or`, `and`, `not`, parentheses, predicates.
+/// Predicates are `process:VALUE`, `window:VALUE` (case-insensitive substring match),
+/// `afk:true`/`afk:false` and `duration<op><value>` where `<op>` is one of `>`, `>=`, `<`, `<=`,
+/// `=` and `<value>` is e.g. `30s`, `5m`, `1h`. Values can be bare words or `"quoted strings"`.
+pub fn parse_query(input: &str) -> Result<QueryExpr> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens: &tokens, position: 0 };
+    let expr = parser.parse_or()?;
+    if parser.position != parser.tokens.len() {
+        bail!("Unexpected trailing input in query at token {}", parser.position);
+    }
+    Ok(expr)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Colon,
+    Comparator(Comparator),
+    Word(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            ':' => {
+                chars.next();
+                tokens.push(Token::Colon);
+            }
+            '>' | '<' | '=' => {
+                chars.next();
+                let comparator = if chars.peek() == Some(&'=') {
+                    chars.next();
+                    match c {
+                        '>' => Comparator::GreaterOrEqual,
+                        '<' => Comparator::LessOrEqual,
+                        _ => Comparator::Equal,
+                    }
+                } else {
+                    match c {
+                        '>' => Comparator::Greater,
+                        '<' => Comparator::Less,
+                        _ => Comparator::Equal,
+                    }
+                };
+                tokens.push(Token::Comparator(comparator));
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => bail!("Unterminated quoted string in query"),
+                    }
+                }
+                tokens.push(Token::Word(value));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | ':' | '>' | '<' | '=' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                match word.as_str() {
+                    "and" => tokens.push(Token::And),
+                    "or" => tokens.push(Token::Or),
+                    "not" => tokens.push(Token::Not),
+                    _ => tokens.push(Token::Word(word)),
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn advance(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpr> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.advance();
+            let right = self.parse_and()?;
+            left = QueryExpr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpr> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.advance();
+            let right = self.parse_unary()?;
+            left = QueryExpr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryExpr> {
+        if self.peek() == Some(&Token::Not) {
+            self.advance();
+            return Ok(QueryExpr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<QueryExpr> {
+        match self.advance() {
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.advance() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(anyhow!("Expected closing parenthesis in query")),
+                }
+            }
+            Some(Token::Word(key)) => self.parse_predicate(key.clone()),
+            other => Err(anyhow!("Expected a predicate in query, found {other:?}")),
+        }
+    }
+
+    fn parse_predicate(&mut self, key: String) -> Result<QueryExpr> {
+        match key.as_str() {
+            "process" | "window" => {
+                self.expect_colon()?;
+                let value = self.expect_word()?;
+                if key == "process" {
+                    Ok(QueryExpr::Process(value))
+                } else {
+                    Ok(QueryExpr::Window(value))
+                }
+            }
+            "afk" => {
+                self.expect_colon()?;
+                let value = self.expect_word()?;
+                let value = value
+                    .parse::<bool>()
+                    .with_context(|| format!("Expected true/false after afk:, got {value}"))?;
+                Ok(QueryExpr::Afk(value))
+            }
+            "duration" => {
+                let comparator = match self.advance() {
+                    Some(Token::Comparator(comparator)) => *comparator,
+                    other => bail!("Expected a comparator (>, >=, <, <=, =) after duration, found {other:?}"),
+                };
+                let value = self.expect_word()?;
+                Ok(QueryExpr::Duration(comparator, parse_duration(&value)?))
+            }
+            other => bail!("Unknown query predicate `{other}`, expected process/window/afk/duration"),
+        }
+    }
+
+    fn expect_colon(&mut self) -> Result<()> {
+        match self.advance() {
+            Some(Token::Colon) => Ok(()),
+            other => Err(anyhow!("Expected ':' in query, found {other:?}")),
+        }
+    }
+
+    fn expect_word(&mut self) -> Result<String> {
+        match self.advance() {
+            Some(Token::Word(value)) => Ok(value.clone()),
+            other => Err(anyhow!("Expected a value in query, found {other:?}")),
+        }
+    }
+}
+
+/// Parses durations like `30s`, `5m`, `1h`, `2d`.
+fn parse_duration(value: &str) -> Result<Duration> {
+    if value.is_empty() {
+        bail!("Expected a duration like `30m`, got an empty value");
+    }
+    let (last_char_index, _) = value
+        .char_indices()
+        .next_back()
+        .expect("value is non-empty, checked above");
+    let (number, unit) = value.split_at(last_char_index);
+    let amount: i64 = number
+        .parse()
+        .with_context(|| format!("Expected a number before the unit in duration `{value}`"))?;
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        _ => bail!("Unknown duration unit `{unit}` in `{value}`, expected one of s/m/h/d"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn entity(process: &str, window: &str, afk: bool, duration_secs: i64) -> UsageIntervalEntity {
+        UsageIntervalEntity {
+            window_name: Arc::from(window),
+            process_name: Arc::from(process),
+            start: Utc.timestamp_opt(0, 0).unwrap(),
+            duration: Duration::seconds(duration_secs),
+            afk,
+        }
+    }
+
+    #[test]
+    fn test_process_predicate_is_case_insensitive_substring() {
+        let query = parse_query("process:firefox").unwrap();
+        assert!(query.matches(&entity("Firefox.exe", "GitHub", false, 10)));
+        assert!(!query.matches(&entity("chrome.exe", "GitHub", false, 10)));
+    }
+
+    #[test]
+    fn test_quoted_window_predicate() {
+        let query = parse_query("window:\"git hub\"").unwrap();
+        assert!(query.matches(&entity("firefox", "My Git Hub Page", false, 10)));
+    }
+
+    #[test]
+    fn test_and_or_not_with_parentheses() {
+        let query = parse_query("process:firefox and not (afk:true or duration<30s)").unwrap();
+        assert!(query.matches(&entity("firefox", "github", false, 60)));
+        assert!(!query.matches(&entity("firefox", "github", true, 60)));
+        assert!(!query.matches(&entity("firefox", "github", false, 10)));
+        assert!(!query.matches(&entity("chrome", "github", false, 60)));
+    }
+
+    #[test]
+    fn test_duration_comparators() {
+        assert!(parse_query("duration>=30s").unwrap().matches(&entity("p", "w", false, 30)));
+        assert!(!parse_query("duration>30s").unwrap().matches(&entity("p", "w", false, 30)));
+    }
+
+    #[test]
+    fn test_unknown_predicate_is_rejected() {
+        assert!(parse_query("bogus:1").is_err());
+    }
+
+    #[test]
+    fn test_duration_with_multi_byte_trailing_char_is_rejected_not_panicking() {
+        assert!(parse_query("duration<30m♥").is_err());
+    }
+}
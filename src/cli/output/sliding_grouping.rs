@@ -1,10 +1,11 @@
 use std::{
     fmt::{Debug, Display},
     pin::Pin,
+    str::FromStr,
 };
 
-use anyhow::Result;
-use chrono::{DateTime, Duration, NaiveTime, TimeZone, Timelike, Utc};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Timelike, Utc};
 use clap::ValueEnum;
 use futures::{Stream, StreamExt};
 use now::DateTimeNow;
@@ -77,6 +78,126 @@ impl SlidingInterval {
             TimeOption::Days => Duration::days(self.duration as i64),
         }
     }
+
+    /// Picks the coarsest [TimeOption] that represents `total` exactly, e.g. 3600 seconds becomes
+    /// `1 hour` rather than `60 minutes`. Unlike [Self::new_opt], the resulting duration isn't
+    /// bounded by that unit's usual cap (a composite span like `1h30m` has to come out as `90
+    /// minutes`, which `new_opt` alone would reject) — those caps exist to keep a bare `-d`/`-o`
+    /// pair typed directly on the CLI sane, not to reject an otherwise-valid composite total.
+    /// Returns `None` only if `total` isn't positive; every positive duration is a whole number of
+    /// seconds, so this always finds a unit.
+    fn from_total_duration(total: Duration) -> Option<Self> {
+        const UNITS: [(TimeOption, i64); 5] = [
+            (TimeOption::Weeks, 7 * 24 * 60 * 60),
+            (TimeOption::Days, 24 * 60 * 60),
+            (TimeOption::Hours, 60 * 60),
+            (TimeOption::Minutes, 60),
+            (TimeOption::Seconds, 1),
+        ];
+
+        let total_seconds = total.num_seconds();
+        if total_seconds <= 0 {
+            return None;
+        }
+
+        UNITS.into_iter().find_map(|(time, unit_seconds)| {
+            if total_seconds % unit_seconds != 0 {
+                return None;
+            }
+            Some(Self {
+                duration: (total_seconds / unit_seconds) as u32,
+                time,
+            })
+        })
+    }
+}
+
+/// Parses compact spans like `30m`, `2h`, `1w`, `45s`, and composite spans like `1h30m`, plus the
+/// keywords `hourly`/`daily`/`minutely`/`secondly`, into a [SlidingInterval]. This lets CLI users
+/// write `--interval 30m` instead of the more rigid `-d 30 -o minutes`.
+impl FromStr for SlidingInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let total = match s.trim().to_ascii_lowercase().as_str() {
+            "hourly" => Duration::hours(1),
+            "daily" => Duration::days(1),
+            "minutely" => Duration::minutes(1),
+            "secondly" => Duration::seconds(1),
+            other => parse_compact_duration(other)?,
+        };
+
+        SlidingInterval::from_total_duration(total)
+            .ok_or_else(|| anyhow!("{s:?} doesn't resolve to a supported interval, e.g. 30m, 2h, 1h30m, daily"))
+    }
+}
+
+/// Tokenizes repeated `<int><unit>` pairs (e.g. `1h30m`) into a total [Duration]. A single pair is
+/// just the degenerate one-token case of the same loop.
+fn parse_compact_duration(s: &str) -> Result<Duration> {
+    let mut remaining = s;
+    let mut total = Duration::zero();
+
+    while !remaining.is_empty() {
+        let digits_end = remaining
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| anyhow!("Missing unit (s/m/h/d/w) after {remaining:?} in {s:?}"))?;
+        if digits_end == 0 {
+            bail!("Expected a number before the unit in {s:?}");
+        }
+        let (number, rest) = remaining.split_at(digits_end);
+
+        let unit_end = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let (unit, rest) = rest.split_at(unit_end);
+
+        let value: i64 = number.parse().with_context(|| format!("{number:?} isn't a valid number in {s:?}"))?;
+        total += match unit {
+            "w" | "week" | "weeks" => Duration::weeks(value),
+            "d" | "day" | "days" => Duration::days(value),
+            "h" | "hour" | "hours" => Duration::hours(value),
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(value),
+            "s" | "sec" | "secs" | "second" | "seconds" => Duration::seconds(value),
+            other => bail!("Unknown time unit {other:?} in {s:?}"),
+        };
+
+        remaining = rest;
+    }
+
+    if total.is_zero() {
+        bail!("{s:?} isn't a valid duration, e.g. 30m, 2h, 1h30m");
+    }
+    Ok(total)
+}
+
+/// Resolves a `NaiveDateTime` we've computed as a bucket boundary back into `tz`'s wall clock,
+/// without ever panicking on a DST transition: an ambiguous local time (fall-back) resolves to its
+/// earliest instant so buckets stay monotonically increasing, and a nonexistent local time
+/// (spring-forward gap) is pushed forward to the first instant past the gap.
+fn resolve_local<Tz: TimeZone>(tz: &Tz, naive: NaiveDateTime) -> DateTime<Tz> {
+    match tz.from_local_datetime(&naive) {
+        chrono::LocalResult::Single(resolved) => resolved,
+        chrono::LocalResult::Ambiguous(earliest, _latest) => earliest,
+        chrono::LocalResult::None => advance_past_dst_gap(tz, naive),
+    }
+}
+
+/// `naive` falls inside a DST spring-forward gap and has no corresponding instant in `tz`. Steps
+/// forward a minute at a time until landing on the first wall-clock time that does exist; real DST
+/// gaps are at most a couple of hours, so this always terminates well before `MAX_PROBES`.
+fn advance_past_dst_gap<Tz: TimeZone>(tz: &Tz, naive: NaiveDateTime) -> DateTime<Tz> {
+    const MAX_PROBES: i64 = 4 * 60;
+
+    let mut candidate = naive;
+    for _ in 0..MAX_PROBES {
+        candidate += Duration::minutes(1);
+        if let chrono::LocalResult::Single(resolved) = tz.from_local_datetime(&candidate) {
+            return resolved;
+        }
+    }
+
+    // Unreachable for any real timezone database; avoid panicking regardless by reading the last
+    // probed wall-clock time as UTC and converting that instant into `tz`.
+    tz.from_utc_datetime(&candidate)
 }
 
 /// Creates a start of a timeline that's easier to comprehend.
@@ -89,50 +210,37 @@ pub fn clean_time_start<Tz: TimeZone>(
         TimeOption::Weeks => rough_start.beginning_of_week(),
         TimeOption::Days => rough_start.beginning_of_day(),
         TimeOption::Hours => {
-            let lower_bound = rough_start
-                .with_hour(0)
-                .unwrap()
-                .with_minute(0)
-                .unwrap()
-                .with_second(0)
-                .unwrap()
-                .with_nanosecond(0)
-                .unwrap();
+            let tz = rough_start.timezone();
+            let naive = rough_start.naive_local();
+            let day_start = naive.date().and_hms_opt(0, 0, 0).unwrap();
+
+            let elapsed_hours = (naive - day_start).num_hours();
+            let remainder = elapsed_hours % scale.duration() as i64;
 
-            let duration = rough_start.clone() - lower_bound.clone();
-            let remainder = duration.num_hours() as u32 % scale.duration();
-            lower_bound
-                .clone()
-                .with_hour(rough_start.clone().hour() - remainder)
-                .unwrap()
+            resolve_local(&tz, day_start + Duration::hours(elapsed_hours - remainder))
         }
         TimeOption::Minutes => {
-            let lower_bound = rough_start
-                .with_minute(0)
-                .unwrap()
-                .with_second(0)
-                .unwrap()
-                .with_nanosecond(0)
-                .unwrap();
+            let tz = rough_start.timezone();
+            let naive = rough_start.naive_local();
+            let hour_start = naive.date().and_hms_opt(naive.hour(), 0, 0).unwrap();
+
+            let elapsed_minutes = (naive - hour_start).num_minutes();
+            let remainder = elapsed_minutes % scale.duration() as i64;
 
-            let duration = rough_start.clone() - lower_bound.clone();
-            let remainder = duration.num_minutes() as u32 % scale.duration();
-            lower_bound
-                .with_minute(rough_start.minute() - remainder)
-                .unwrap()
+            resolve_local(&tz, hour_start + Duration::minutes(elapsed_minutes - remainder))
         }
         TimeOption::Seconds => {
-            let lower_bound = rough_start
-                .with_second(0)
-                .unwrap()
-                .with_nanosecond(0)
+            let tz = rough_start.timezone();
+            let naive = rough_start.naive_local();
+            let minute_start = naive
+                .date()
+                .and_hms_opt(naive.hour(), naive.minute(), 0)
                 .unwrap();
 
-            let duration = rough_start.clone() - lower_bound.clone();
-            let remainder = duration.num_seconds() as u32 % scale.duration();
-            lower_bound
-                .with_second(rough_start.second() - remainder)
-                .unwrap()
+            let elapsed_seconds = (naive - minute_start).num_seconds();
+            let remainder = elapsed_seconds % scale.duration() as i64;
+
+            resolve_local(&tz, minute_start + Duration::seconds(elapsed_seconds - remainder))
         }
     }
 }
@@ -167,7 +275,9 @@ where
         let local_start = DateTime::<Tz>::from(start);
         let local_end = DateTime::<Tz>::from(end);
         if local_start.date_naive() != local_end.date_naive() {
-            end = local_end.with_time(NaiveTime::MIN).unwrap().to_utc()
+            let tz = local_end.timezone();
+            let local_midnight = local_end.date_naive().and_hms_opt(0, 0, 0).unwrap();
+            end = resolve_local(&tz, local_midnight).to_utc()
         }
         (start, end)
     };
@@ -272,6 +382,72 @@ async fn take_or_poll_ok<T>(
     }
 }
 
+#[cfg(test)]
+mod sliding_interval_parse_tests {
+    use std::str::FromStr;
+
+    use super::{SlidingInterval, TimeOption};
+
+    #[test]
+    fn parses_compact_single_unit_spans() {
+        let interval = SlidingInterval::from_str("30m").unwrap();
+        assert_eq!(interval.duration(), 30);
+        assert_eq!(*interval.time(), TimeOption::Minutes);
+
+        let interval = SlidingInterval::from_str("2h").unwrap();
+        assert_eq!(interval.duration(), 2);
+        assert_eq!(*interval.time(), TimeOption::Hours);
+
+        let interval = SlidingInterval::from_str("1w").unwrap();
+        assert_eq!(interval.duration(), 1);
+        assert_eq!(*interval.time(), TimeOption::Weeks);
+    }
+
+    #[test]
+    fn parses_composite_spans_into_the_coarsest_unit() {
+        // 1h30m = 90 minutes, which is the coarsest unit that divides it evenly.
+        let interval = SlidingInterval::from_str("1h30m").unwrap();
+        assert_eq!(interval.duration(), 90);
+        assert_eq!(*interval.time(), TimeOption::Minutes);
+    }
+
+    #[test]
+    fn parses_keywords() {
+        assert_eq!(SlidingInterval::from_str("hourly").unwrap().duration(), 1);
+        assert_eq!(*SlidingInterval::from_str("daily").unwrap().time(), TimeOption::Days);
+        assert_eq!(*SlidingInterval::from_str("minutely").unwrap().time(), TimeOption::Minutes);
+        assert_eq!(*SlidingInterval::from_str("secondly").unwrap().time(), TimeOption::Seconds);
+    }
+
+    #[test]
+    fn new_opt_still_enforces_its_bounds_for_bare_d_o_flags() {
+        // Unlike the compact string parser, a direct `-d`/`-o` pair is still capped, e.g. 90
+        // minutes or 24 hours aren't valid single-unit values there.
+        assert!(SlidingInterval::new_opt(90, TimeOption::Minutes).is_none());
+        assert!(SlidingInterval::new_opt(24, TimeOption::Hours).is_none());
+    }
+
+    #[test]
+    fn compact_single_unit_and_composite_spans_with_the_same_total_agree() {
+        // "90m" and "1h30m" both total 5400 seconds; from_total_duration doesn't distinguish how
+        // the total was written, so both resolve to the same coarsest-unit representation even
+        // though 90 exceeds new_opt's usual bound for Minutes.
+        let from_single = SlidingInterval::from_str("90m").unwrap();
+        let from_composite = SlidingInterval::from_str("1h30m").unwrap();
+        assert_eq!(from_single.duration(), 90);
+        assert_eq!(*from_single.time(), TimeOption::Minutes);
+        assert_eq!(from_composite.duration(), from_single.duration());
+        assert_eq!(*from_composite.time(), *from_single.time());
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(SlidingInterval::from_str("").is_err());
+        assert!(SlidingInterval::from_str("m30").is_err());
+        assert!(SlidingInterval::from_str("30x").is_err());
+    }
+}
+
 #[cfg(test)]
 mod clean_time_tests {
     use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
@@ -365,6 +541,87 @@ mod clean_time_tests {
     }
 }
 
+#[cfg(test)]
+mod dst_tests {
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+    use chrono_tz::America::New_York;
+
+    use super::{SlidingInterval, TimeOption, clean_time_start, resolve_local};
+
+    /// 2024-03-10 is the US spring-forward transition in `America/New_York`: local clocks jump
+    /// from 01:59:59 straight to 03:00:00, so every wall-clock time in `[02:00, 03:00)` doesn't
+    /// exist. `resolve_local` has to land past the gap instead of panicking.
+    #[test]
+    fn resolve_local_pushes_forward_out_of_a_spring_forward_gap() {
+        let naive = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+            NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
+        );
+
+        let resolved = resolve_local(&New_York, naive);
+
+        // Landed at or after the gap's far edge, never inside it.
+        assert!(resolved.naive_local() >= NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+            NaiveTime::from_hms_opt(3, 0, 0).unwrap(),
+        ));
+    }
+
+    /// 2024-11-03 is the US fall-back transition in `America/New_York`: 01:00:00-01:59:59 occurs
+    /// twice (once in EDT, once in EST). `resolve_local` should consistently pick the earliest of
+    /// the two instants so repeated bucket boundaries stay monotonically increasing.
+    #[test]
+    fn resolve_local_picks_the_earliest_instant_across_a_fall_back() {
+        let naive = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 11, 3).unwrap(),
+            NaiveTime::from_hms_opt(1, 30, 0).unwrap(),
+        );
+
+        let resolved = resolve_local(&New_York, naive);
+        let both = New_York.from_local_datetime(&naive);
+        let chrono::LocalResult::Ambiguous(earliest, latest) = both else {
+            panic!("expected {naive} to be ambiguous in America/New_York, got {both:?}");
+        };
+
+        assert_eq!(resolved, earliest);
+        assert!(earliest < latest);
+    }
+
+    /// Bucketing hourly through the spring-forward gap should never panic and should keep
+    /// producing strictly increasing bucket starts, even though an hour of wall-clock time never
+    /// happened.
+    #[test]
+    fn clean_time_start_is_monotonic_across_a_spring_forward_gap() {
+        let scale = SlidingInterval::new_opt(1, TimeOption::Hours).unwrap();
+        let before = New_York
+            .from_local_datetime(&NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+                NaiveTime::from_hms_opt(1, 30, 0).unwrap(),
+            ))
+            .unwrap();
+        let during_gap = resolve_local(
+            &New_York,
+            NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+                NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
+            ),
+        );
+        let after = New_York
+            .from_local_datetime(&NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+                NaiveTime::from_hms_opt(4, 30, 0).unwrap(),
+            ))
+            .unwrap();
+
+        let bucket_before = clean_time_start(before, &scale);
+        let bucket_during = clean_time_start(during_gap, &scale);
+        let bucket_after = clean_time_start(after, &scale);
+
+        assert!(bucket_before <= bucket_during);
+        assert!(bucket_during <= bucket_after);
+    }
+}
+
 #[cfg(test)]
 mod sliding_groupnig_test {
     use std::convert::identity;
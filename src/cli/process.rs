@@ -5,10 +5,10 @@ use std::{
 };
 
 use anyhow::{anyhow, Result};
-use sysinfo::{Signal, System, get_current_pid};
+use sysinfo::{Pid, Signal, System, get_current_pid};
 use tracing::error;
 
-use crate::cli::daemon_path::to_daemon_path;
+use crate::{cli::daemon_path::to_daemon_path, daemon::pid_file};
 
 
 /// Returns all the running processes that aren't this process. Realistically there should only one
@@ -44,6 +44,44 @@ pub fn kill_previous_daemons(name: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Stops the daemon identified by the PID file in `app_dir`, instead of pattern-matching process
+/// names. A missing process (stale PID file left behind by an unclean previous exit) is treated as
+/// already-stopped rather than an error. Removes the PID file either way.
+///
+/// The OS is free to reuse a PID once its process exits, so a stale PID file (left behind by a
+/// crash, OOM-kill, or power loss) could otherwise point at some unrelated process by the time we
+/// read it. Like `kill_previous_daemons`, we only act on it once `process.exe()` confirms it's
+/// actually the whatawhat daemon binary.
+pub fn stop_daemon_via_pid_file(app_dir: &Path) -> Result<()> {
+    let pid = pid_file::read_pid_file(app_dir)?;
+    let system = System::new_all();
+    let daemon_name = to_daemon_path(env::current_exe().expect("Can't operate without an executable"));
+
+    match system.process(Pid::from_u32(pid)) {
+        Some(process) if process.exe().is_some_and(|exe| exe == daemon_name) => {
+            println!("Stopping daemon process {pid}");
+            if process.kill_with(Signal::Term).is_none() {
+                // Windows doesn't support Signals, so forced termination is the only simple option.
+                if !process.kill() {
+                    return Err(anyhow!("Failed killing process {pid}"));
+                }
+            }
+            process.wait();
+        },
+        Some(_) => {
+            println!(
+                "Process {pid} is no longer the whatawhat daemon, treating daemon as already stopped"
+            );
+        },
+        None => {
+            println!("No running process with pid {pid}, treating daemon as already stopped");
+        },
+    }
+
+    pid_file::remove_pid_file(app_dir);
+    Ok(())
+}
+
 /// Intended for shutting down previous daemon and starting new one. Currently for simplicity sake
 /// it operates using a detached process. This is not great but it's not as hard to configure.
 pub fn restart_daemon() -> Result<()> {
@@ -0,0 +1,258 @@
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+use anyhow::Result;
+use axum::{
+    Router,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::{Duration, NaiveDate};
+use clap::Parser;
+use futures::{Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    daemon::storage::{entities::UsageIntervalEntity, record_storage::RecordStorageImpl},
+    utils::csv,
+};
+
+use super::create_application_default_path;
+
+/// Exposes usage data over HTTP, so dashboards and scripts can pull it without parsing record
+/// files themselves.
+#[derive(Debug, Parser)]
+pub struct ServeCommand {
+    #[arg(
+        long,
+        default_value = "127.0.0.1:9898",
+        help = "Address the query server listens on"
+    )]
+    addr: SocketAddr,
+}
+
+struct ServerState {
+    storage: RecordStorageImpl,
+}
+
+#[derive(Debug, Deserialize)]
+struct RangeQuery {
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+impl RangeQuery {
+    /// `stream_range` iterates whole-day shards inclusive of both ends, so the dates as given are
+    /// already the right bounds to pass it; no shifting needed.
+    fn into_bounds(self) -> (NaiveDate, NaiveDate) {
+        (self.from, self.to)
+    }
+}
+
+pub async fn process_serve_command(ServeCommand { addr }: ServeCommand) -> Result<()> {
+    let storage = RecordStorageImpl::new(create_application_default_path()?.join("records"))?;
+    let state = Arc::new(ServerState { storage });
+
+    let router = Router::new()
+        .route("/usage", get(get_usage))
+        .route("/usage/by-process", get(get_usage_by_process))
+        .route("/usage/by-window", get(get_usage_by_window))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("Listening on {addr}");
+    axum::serve(listener, router).await?;
+    Ok(())
+}
+
+/// Wraps errors so they map to the right HTTP status instead of always 500.
+struct ApiError(StatusCode, anyhow::Error);
+
+impl From<anyhow::Error> for ApiError {
+    fn from(value: anyhow::Error) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, value)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, self.1.to_string()).into_response()
+    }
+}
+
+/// `GET /usage?from=<date>&to=<date>`: returns the raw intervals in range as JSON, or CSV if the
+/// client asks for it via `Accept: text/csv`. Storage is read day by day through `stream_range`,
+/// but the response body is built in full before it is sent; it is not streamed to the client.
+async fn get_usage(
+    State(state): State<Arc<ServerState>>,
+    headers: axum::http::HeaderMap,
+    Query(range): Query<RangeQuery>,
+) -> Result<Response, ApiError> {
+    let (from, to) = range.into_bounds();
+    let intervals = state.storage.stream_range(from, to);
+
+    let wants_csv = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/csv"));
+
+    if wants_csv {
+        Ok(render_csv(intervals).await.into_response())
+    } else {
+        Ok(render_json(intervals).await.into_response())
+    }
+}
+
+async fn render_json(
+    intervals: impl Stream<Item = Result<UsageIntervalEntity>>,
+) -> Response {
+    let values: Vec<UsageIntervalEntity> = match intervals.try_collect().await {
+        Ok(v) => v,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    axum::Json(values).into_response()
+}
+
+async fn render_csv(intervals: impl Stream<Item = Result<UsageIntervalEntity>>) -> Response {
+    let mut out = String::from("process_name,window_name,start,duration_seconds,afk\n");
+    let mut intervals = std::pin::pin!(intervals);
+    while let Some(next) = intervals.next().await {
+        match next {
+            Ok(v) => out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                csv::escape(&v.process_name),
+                csv::escape(&v.window_name),
+                v.start.to_rfc3339(),
+                v.duration.num_seconds(),
+                v.afk,
+            )),
+            Err(e) => return ApiError::from(e).into_response(),
+        }
+    }
+    ([(header::CONTENT_TYPE, "text/csv")], out).into_response()
+}
+
+/// `GET /usage/by-process?from=<date>&to=<date>`: durations grouped by process, summed server
+/// side from the range stream so the client never has to aggregate raw intervals itself.
+async fn get_usage_by_process(
+    State(state): State<Arc<ServerState>>,
+    Query(range): Query<RangeQuery>,
+) -> Result<axum::Json<Vec<GroupedUsage>>, ApiError> {
+    let (from, to) = range.into_bounds();
+    let grouped = group_by(state.storage.stream_range(from, to), |v| {
+        v.process_name.to_string()
+    })
+    .await?;
+    Ok(axum::Json(grouped))
+}
+
+/// `GET /usage/by-window?from=<date>&to=<date>`: durations grouped by window title.
+async fn get_usage_by_window(
+    State(state): State<Arc<ServerState>>,
+    Query(range): Query<RangeQuery>,
+) -> Result<axum::Json<Vec<GroupedUsage>>, ApiError> {
+    let (from, to) = range.into_bounds();
+    let grouped = group_by(state.storage.stream_range(from, to), |v| {
+        v.window_name.to_string()
+    })
+    .await?;
+    Ok(axum::Json(grouped))
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct GroupedUsage {
+    key: String,
+    duration_seconds: i64,
+}
+
+async fn group_by(
+    intervals: impl Stream<Item = Result<UsageIntervalEntity>>,
+    key_of: impl Fn(&UsageIntervalEntity) -> String,
+) -> Result<Vec<GroupedUsage>> {
+    let mut totals: HashMap<String, Duration> = HashMap::new();
+    let mut intervals = std::pin::pin!(intervals);
+    while let Some(next) = intervals.next().await {
+        let interval = next?;
+        *totals.entry(key_of(&interval)).or_insert_with(Duration::zero) += interval.duration;
+    }
+
+    let mut grouped: Vec<GroupedUsage> = totals
+        .into_iter()
+        .map(|(key, duration)| GroupedUsage {
+            key,
+            duration_seconds: duration.num_seconds(),
+        })
+        .collect();
+    grouped.sort_by(|a, b| b.duration_seconds.cmp(&a.duration_seconds));
+    Ok(grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use futures::stream;
+
+    use super::*;
+
+    fn sample_interval(process_name: &str, window_name: &str, seconds: i64) -> UsageIntervalEntity {
+        UsageIntervalEntity {
+            window_name: window_name.into(),
+            process_name: process_name.into(),
+            start: Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap(),
+            duration: Duration::seconds(seconds),
+            afk: false,
+        }
+    }
+
+    #[test]
+    fn into_bounds_is_day_inclusive_on_both_ends() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let range = RangeQuery { from: day, to: day };
+        assert_eq!(range.into_bounds(), (day, day));
+    }
+
+    /// Regression test: `into_bounds` used to advance `to` by a day before handing it to
+    /// `stream_range`, which already treats `to` as inclusive, leaking an extra day of data past
+    /// what the caller asked for.
+    #[test]
+    fn into_bounds_does_not_shift_a_multi_day_range_past_to() {
+        let from = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let range = RangeQuery { from, to };
+        assert_eq!(range.into_bounds(), (from, to));
+    }
+
+    #[tokio::test]
+    async fn group_by_sums_durations_per_key_and_sorts_descending() {
+        let intervals = stream::iter(vec![
+            Ok(sample_interval("firefox", "GitHub", 60)),
+            Ok(sample_interval("firefox", "GitHub", 30)),
+            Ok(sample_interval("alacritty", "vim", 120)),
+        ]);
+
+        let grouped = group_by(intervals, |v| v.process_name.to_string()).await.unwrap();
+
+        assert_eq!(
+            grouped,
+            vec![
+                GroupedUsage { key: "alacritty".into(), duration_seconds: 120 },
+                GroupedUsage { key: "firefox".into(), duration_seconds: 90 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn render_csv_writes_header_and_quotes_embedded_commas_and_newlines() {
+        let intervals = stream::iter(vec![Ok(sample_interval("firefox", "a,b\nc", 42))]);
+        let response = render_csv(intervals).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert_eq!(
+            body.lines().next().unwrap(),
+            "process_name,window_name,start,duration_seconds,afk"
+        );
+        assert!(body.contains("firefox,\"a,b\nc\","));
+    }
+}
@@ -0,0 +1,299 @@
+use anyhow::Result;
+use clap::Subcommand;
+
+/// Name systemd/launchd/the Windows SCM know the daemon by, once installed.
+const SERVICE_NAME: &str = "whatawhat";
+
+#[derive(Debug, Subcommand)]
+pub enum ServiceCommand {
+    #[command(about = "Install and start the daemon as an auto-starting OS service")]
+    Install {},
+    #[command(about = "Stop and remove the installed OS service")]
+    Uninstall {},
+    #[command(about = "Start the installed OS service")]
+    Start {},
+    #[command(about = "Stop the installed OS service")]
+    Stop {},
+    #[command(about = "Print the installed OS service's status")]
+    Status {},
+}
+
+/// Registers/drives the daemon through the platform's native service manager, so it survives
+/// logout and reboot instead of needing `restart`/`stop` to be re-run by hand every session.
+pub fn process_service_command(command: ServiceCommand) -> Result<()> {
+    match command {
+        ServiceCommand::Install {} => install(),
+        ServiceCommand::Uninstall {} => uninstall(),
+        ServiceCommand::Start {} => start(),
+        ServiceCommand::Stop {} => stop(),
+        ServiceCommand::Status {} => status(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{fs, path::PathBuf, process::Command};
+
+    use anyhow::{bail, Context, Result};
+
+    use crate::cli::{create_application_default_path, daemon_path::to_daemon_path};
+
+    use super::SERVICE_NAME;
+
+    fn unit_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").context("HOME should be set")?;
+        Ok(PathBuf::from(home).join(".config/systemd/user").join(format!("{SERVICE_NAME}.service")))
+    }
+
+    fn systemctl(args: &[&str]) -> Result<()> {
+        let mut full_args = vec!["--user"];
+        full_args.extend_from_slice(args);
+        let status = Command::new("systemctl")
+            .args(&full_args)
+            .status()
+            .context("Failed to invoke systemctl")?;
+        if !status.success() {
+            bail!("systemctl {full_args:?} exited with {status}");
+        }
+        Ok(())
+    }
+
+    pub fn install() -> Result<()> {
+        let daemon_path = to_daemon_path(std::env::current_exe()?);
+        let app_dir = create_application_default_path()?;
+        let unit_path = unit_path()?;
+        fs::create_dir_all(unit_path.parent().expect("unit path always has a parent"))?;
+
+        let unit = format!(
+            "[Unit]\nDescription=Whatawhat activity monitor\n\n\
+             [Service]\nExecStart={} --dir {}\nRestart=on-failure\n\n\
+             [Install]\nWantedBy=default.target\n",
+            daemon_path.display(),
+            app_dir.display(),
+        );
+        fs::write(&unit_path, unit).context("Failed to write systemd unit file")?;
+
+        systemctl(&["daemon-reload"])?;
+        systemctl(&["enable", "--now", &format!("{SERVICE_NAME}.service")])?;
+        println!("Installed and started the {SERVICE_NAME} systemd user service");
+        Ok(())
+    }
+
+    pub fn uninstall() -> Result<()> {
+        systemctl(&["disable", "--now", &format!("{SERVICE_NAME}.service")]).ok();
+        let unit_path = unit_path()?;
+        if unit_path.exists() {
+            fs::remove_file(&unit_path).context("Failed to remove systemd unit file")?;
+        }
+        systemctl(&["daemon-reload"])?;
+        println!("Uninstalled the {SERVICE_NAME} systemd user service");
+        Ok(())
+    }
+
+    pub fn start() -> Result<()> {
+        systemctl(&["start", &format!("{SERVICE_NAME}.service")])
+    }
+
+    pub fn stop() -> Result<()> {
+        systemctl(&["stop", &format!("{SERVICE_NAME}.service")])
+    }
+
+    pub fn status() -> Result<()> {
+        Command::new("systemctl")
+            .args(["--user", "status", &format!("{SERVICE_NAME}.service")])
+            .status()
+            .context("Failed to invoke systemctl")?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{fs, path::PathBuf, process::Command};
+
+    use anyhow::{Context, Result};
+
+    use crate::cli::{create_application_default_path, daemon_path::to_daemon_path};
+
+    use super::SERVICE_NAME;
+
+    fn label() -> String {
+        format!("com.{SERVICE_NAME}.daemon")
+    }
+
+    fn plist_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").context("HOME should be set")?;
+        Ok(PathBuf::from(home)
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", label())))
+    }
+
+    pub fn install() -> Result<()> {
+        let daemon_path = to_daemon_path(std::env::current_exe()?);
+        let app_dir = create_application_default_path()?;
+        let plist_path = plist_path()?;
+        fs::create_dir_all(plist_path.parent().expect("plist path always has a parent"))?;
+
+        let plist = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\"><dict>\n\
+             <key>Label</key><string>{label}</string>\n\
+             <key>ProgramArguments</key><array>\n\
+             <string>{daemon}</string><string>--dir</string><string>{dir}</string>\n\
+             </array>\n\
+             <key>RunAtLoad</key><true/>\n\
+             <key>KeepAlive</key><true/>\n\
+             </dict></plist>\n",
+            label = label(),
+            daemon = daemon_path.display(),
+            dir = app_dir.display(),
+        );
+        fs::write(&plist_path, plist).context("Failed to write launchd plist")?;
+
+        Command::new("launchctl")
+            .args(["load", "-w"])
+            .arg(&plist_path)
+            .status()
+            .context("Failed to invoke launchctl load")?;
+        println!("Installed and loaded the {} launchd agent", label());
+        Ok(())
+    }
+
+    pub fn uninstall() -> Result<()> {
+        let plist_path = plist_path()?;
+        Command::new("launchctl")
+            .args(["unload", "-w"])
+            .arg(&plist_path)
+            .status()
+            .ok();
+        if plist_path.exists() {
+            fs::remove_file(&plist_path).context("Failed to remove launchd plist")?;
+        }
+        println!("Uninstalled the {} launchd agent", label());
+        Ok(())
+    }
+
+    pub fn start() -> Result<()> {
+        Command::new("launchctl")
+            .args(["start", &label()])
+            .status()
+            .context("Failed to invoke launchctl start")?;
+        Ok(())
+    }
+
+    pub fn stop() -> Result<()> {
+        Command::new("launchctl")
+            .args(["stop", &label()])
+            .status()
+            .context("Failed to invoke launchctl stop")?;
+        Ok(())
+    }
+
+    pub fn status() -> Result<()> {
+        Command::new("launchctl")
+            .args(["list", &label()])
+            .status()
+            .context("Failed to invoke launchctl list")?;
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::process::Command;
+
+    use anyhow::{Context, Result};
+
+    use crate::cli::{create_application_default_path, daemon_path::to_daemon_path};
+
+    use super::SERVICE_NAME;
+
+    /// Shells out to `sc.exe` rather than calling `CreateService`/`OpenSCManager` through the
+    /// `windows` crate directly, to stay consistent with how the rest of this CLI talks to
+    /// platform process/service managers (see `systemctl`/`launchctl` in the sibling platform
+    /// modules, and `swaymsg` in `window_api::x11`).
+    fn sc(args: &[&str]) -> Result<()> {
+        Command::new("sc.exe")
+            .args(args)
+            .status()
+            .context("Failed to invoke sc.exe")?;
+        Ok(())
+    }
+
+    pub fn install() -> Result<()> {
+        let daemon_path = to_daemon_path(std::env::current_exe()?);
+        let app_dir = create_application_default_path()?;
+        // Quoted so `sc.exe`/the SCM don't misparse either path at a space, e.g. the default
+        // `C:\Program Files\...` install location or a `--dir` under a space-containing folder.
+        let bin_path = format!("\"{}\" --dir \"{}\"", daemon_path.display(), app_dir.display());
+        sc(&["create", SERVICE_NAME, "binPath=", &bin_path, "start=", "auto"])?;
+        sc(&["start", SERVICE_NAME])?;
+        println!("Installed and started the {SERVICE_NAME} Windows service");
+        Ok(())
+    }
+
+    pub fn uninstall() -> Result<()> {
+        sc(&["stop", SERVICE_NAME]).ok();
+        sc(&["delete", SERVICE_NAME])?;
+        println!("Uninstalled the {SERVICE_NAME} Windows service");
+        Ok(())
+    }
+
+    pub fn start() -> Result<()> {
+        sc(&["start", SERVICE_NAME])
+    }
+
+    pub fn stop() -> Result<()> {
+        sc(&["stop", SERVICE_NAME])
+    }
+
+    pub fn status() -> Result<()> {
+        sc(&["query", SERVICE_NAME])
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use anyhow::{bail, Result};
+
+    pub fn install() -> Result<()> {
+        bail!("Service management isn't supported on this platform yet")
+    }
+
+    pub fn uninstall() -> Result<()> {
+        bail!("Service management isn't supported on this platform yet")
+    }
+
+    pub fn start() -> Result<()> {
+        bail!("Service management isn't supported on this platform yet")
+    }
+
+    pub fn stop() -> Result<()> {
+        bail!("Service management isn't supported on this platform yet")
+    }
+
+    pub fn status() -> Result<()> {
+        bail!("Service management isn't supported on this platform yet")
+    }
+}
+
+fn install() -> Result<()> {
+    platform::install()
+}
+
+fn uninstall() -> Result<()> {
+    platform::uninstall()
+}
+
+fn start() -> Result<()> {
+    platform::start()
+}
+
+fn stop() -> Result<()> {
+    platform::stop()
+}
+
+fn status() -> Result<()> {
+    platform::status()
+}
@@ -0,0 +1,42 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+
+use crate::daemon::status::{status_file_path, StatusSnapshot};
+
+use super::{create_application_default_path, timeline::format_duration};
+
+/// Reads the daemon's persisted [StatusSnapshot] and prints a short health summary, so a user can
+/// tell whether the collector is alive without grepping the hourly rotated log files.
+pub fn process_status_command() -> Result<()> {
+    let app_dir = create_application_default_path()?;
+    let path = status_file_path(&app_dir);
+
+    let contents = fs::read_to_string(&path).with_context(|| {
+        format!(
+            "No status file found at {path:?}. Is the daemon running? (start it with `whatawhat restart`)"
+        )
+    })?;
+    let snapshot: StatusSnapshot =
+        serde_json::from_str(&contents).context("Failed to parse status file")?;
+
+    let now = Utc::now();
+    let uptime = now - snapshot.started_at;
+    let since_last_event = snapshot.last_event_at.map(|v| now - v);
+
+    println!("Instance:         {}", snapshot.instance_id);
+    println!("Version:          {}", snapshot.version);
+    println!("Uptime:           {}", format_duration(uptime));
+    println!("RSS:              {} MiB", snapshot.rss_bytes / 1024 / 1024);
+    println!("CPU:              {:.1}%", snapshot.cpu_usage_percent);
+    println!("Events processed: {}", snapshot.events_processed);
+    println!("Events errored:   {}", snapshot.events_errored);
+    match since_last_event {
+        Some(since) => println!("Last event:       {} ago", format_duration(since)),
+        None => println!("Last event:       never"),
+    }
+    println!("Last refreshed:   {} ago", format_duration(now - snapshot.updated_at));
+
+    Ok(())
+}
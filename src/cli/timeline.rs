@@ -1,15 +1,17 @@
 use std::{fmt::Display, path::PathBuf};
 
 use anyhow::Result;
-use chrono::{DateTime, Duration, Local};
+use chrono::{DateTime, Duration, Local, Months, TimeZone, Utc};
 use chrono_english::parse_date_string;
 use clap::{CommandFactory, Parser, ValueEnum};
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStreamExt, future};
 use now::DateTimeNow;
+use serde::Serialize;
 
 use crate::{
     daemon::storage::{entities::UsageIntervalEntity, record_storage::RecordStorageImpl},
     utils::{
+        csv,
         percentage::{Percentage, duration_percentage},
         time::next_day_start,
     },
@@ -19,14 +21,16 @@ use super::{
     Args, create_application_default_path,
     output::{
         self,
-        analysis::{analyze_processes, analyze_windows},
+        analysis::{ProcessUsage, WindowUsage, analyze_processes, analyze_windows},
+        chart::{ChartStyle, render_process_timeline},
         extract_between,
+        query::{QueryExpr, parse_query},
         sliding_grouping::{SlidingInterval, TimeOption, sliding_interval_grouping},
     },
 };
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
-enum DateStyle {
+pub(crate) enum DateStyle {
     Uk,
     Us,
 }
@@ -49,28 +53,97 @@ impl Display for DateStyle {
     }
 }
 
-#[derive(Debug, Parser)]
-pub struct TimelineCommand {
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+    /// A stacked bar or sparkline chart rendered to the terminal instead of a table of rows. Only
+    /// supported for the per-interval process timeline (`--processes`, not `--summary`).
+    Chart,
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Table => write!(f, "table"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Chart => write!(f, "chart"),
+        }
+    }
+}
+
+/// `--chart-top`/`--chart-width`, flattened into any command whose `--output chart` needs tuning.
+#[derive(Debug, Clone, Copy, clap::Args)]
+pub(crate) struct ChartArgs {
+    #[arg(
+        long = "chart-top",
+        default_value_t = 5,
+        help = "Number of top processes to show individually with --output chart; the rest are folded into \"Other\""
+    )]
+    pub(crate) top: usize,
+    #[arg(
+        long = "chart-width",
+        help = "Terminal width to render --output chart at. Auto-detected from the terminal when omitted"
+    )]
+    pub(crate) width: Option<u16>,
+    #[arg(
+        long = "chart-style",
+        default_value_t = ChartStyle::Bar,
+        help = "Chart style for --output chart: a labeled stacked bar per bucket, or a single-line sparkline overview"
+    )]
+    pub(crate) style: ChartStyle,
+}
+
+/// The timezone that bucket boundaries (`clean_time_start`/`sliding_interval_grouping`) are
+/// computed against. `Local` is the system's configured zone, which is what most users want since
+/// it makes e.g. day-sized buckets line up with local midnight.
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub(crate) enum TimezoneOption {
+    Local,
+    Utc,
+}
+
+impl Display for TimezoneOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimezoneOption::Local => write!(f, "local"),
+            TimezoneOption::Utc => write!(f, "utc"),
+        }
+    }
+}
+
+/// Shared `--start`/`--end`/`--date-style`/`--days` range parsing, flattened into any command
+/// that needs to select a range of recorded activity (e.g. [TimelineCommand] and `export influx`).
+#[derive(Debug, Clone, clap::Args)]
+pub(crate) struct DateRangeArgs {
     #[arg(
         long = "start",
         short,
         help = "Start of the range. Examples are \"yesterday\", \"1 hour ago\", \"15/03/2025\", \"12:00 16/03/2025\", \"12 AM 16/03/2025\""
     )]
-    start_date: Option<String>,
+    pub(crate) start_date: Option<String>,
     #[arg(
         long = "end",
         short,
         help = "End of the range. Examples are \"yesterday\", \"1 hour ago\", \"15/03/2025\", \"12:00 16/03/2025\", \"12 AM 16/03/2025\""
     )]
-    end_date: Option<String>,
+    pub(crate) end_date: Option<String>,
     #[arg(long, default_value_t = DateStyle::Uk, help = "Style of dates used during parsing. For Uk it's day/month/year. For Us it's month/day/year")]
-    date_style: DateStyle,
+    pub(crate) date_style: DateStyle,
     #[arg(
         long = "days",
         default_value_t = false,
         help = "Take inputs as whole days. For example if start and end are both 15/03/2025 this option allows to extract the whole day"
     )]
-    treat_as_days: bool,
+    pub(crate) treat_as_days: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct TimelineCommand {
+    #[command(flatten)]
+    range: DateRangeArgs,
     #[command(flatten)]
     interval: PrintInterval,
     #[arg(short = 'p', long = "percentage", help = "Filter apps to have at least specified percentage", default_value_t = Percentage::new_opt(1.).unwrap()) ]
@@ -84,6 +157,27 @@ pub struct TimelineCommand {
         help = "Include time afk. Person is considered afk after 2 minutes of idle time."
     )]
     afk: bool,
+
+    #[arg(
+        long,
+        help = "Instead of a per-interval timeline, print one rolled-up total per process/window for the whole range"
+    )]
+    summary: bool,
+
+    #[arg(
+        long,
+        help = "Filter intervals before reporting, e.g. `process:firefox and not afk:true`. Predicates are process:VALUE, window:VALUE (case-insensitive substring match), afk:true/false and duration>30s, combined with and/or/not and parentheses"
+    )]
+    query: Option<String>,
+
+    #[arg(short = 'O', long = "output", default_value_t = OutputFormat::Table, help = "Output format")]
+    output: OutputFormat,
+
+    #[arg(long = "timezone", default_value_t = TimezoneOption::Local, help = "Timezone bucket boundaries (e.g. day/week starts) are computed against")]
+    timezone: TimezoneOption,
+
+    #[command(flatten)]
+    chart: ChartArgs,
 }
 
 #[derive(Parser, Debug)]
@@ -97,14 +191,43 @@ struct DaemonParams {
 pub struct PrintInterval {
     #[arg(
         short,
-        help = "Duration of interval. Combines with option to create interval -d 15 -o minutes"
+        help = "Duration of interval. Combines with option to create interval -d 15 -o minutes. Mutually exclusive with --interval",
+        conflicts_with = "compact"
     )]
-    duration: u32,
+    duration: Option<u32>,
     #[arg(
         short,
-        help = "Time option of interval. Combines with option to create interval -d 15 -o minutes"
+        help = "Time option of interval. Combines with duration to create interval -d 15 -o minutes. Mutually exclusive with --interval",
+        conflicts_with = "compact"
     )]
-    option: TimeOption,
+    option: Option<TimeOption>,
+    #[arg(
+        short = 'i',
+        long = "interval",
+        help = "Interval as a compact span, e.g. `30m`, `2h`, `1h30m`, `1w`, or one of hourly/daily/minutely/secondly. Alternative to -d/-o"
+    )]
+    compact: Option<SlidingInterval>,
+}
+
+impl PrintInterval {
+    /// Resolves either `--interval` or the `-d`/`-o` pair into a concrete [SlidingInterval].
+    fn resolve(self) -> Result<SlidingInterval, clap::Error> {
+        match (self.compact, self.duration, self.option) {
+            (Some(interval), None, None) => Ok(interval),
+            (None, Some(duration), Some(option)) => {
+                SlidingInterval::new_opt(duration, option).ok_or_else(|| {
+                    Args::command().error(
+                        clap::error::ErrorKind::ValueValidation,
+                        format!("Can't create an interval using {duration} and {option}"),
+                    )
+                })
+            }
+            _ => Err(Args::command().error(
+                clap::error::ErrorKind::ValueValidation,
+                "Specify either --interval, or both -d and -o",
+            )),
+        }
+    }
 }
 
 const DEFAULT_PRINTED_INTERVALS: i32 = 10;
@@ -113,14 +236,16 @@ const DEFAULT_PRINTED_INTERVALS: i32 = 10;
 /// about user activity from `start_date` to `end_date`.
 pub async fn process_timeline_command(
     TimelineCommand {
-        start_date,
-        end_date,
-        date_style,
+        range,
         interval,
-        treat_as_days,
         min_percentage,
         use_processes,
         afk,
+        summary,
+        query,
+        output,
+        timezone,
+        chart,
     }: TimelineCommand,
 ) -> Result<()> {
     let ParamParseResult {
@@ -128,11 +253,77 @@ pub async fn process_timeline_command(
         start,
         end,
         show_time,
-    } = match parse_values(start_date, end_date, date_style, interval, treat_as_days) {
+    } = match parse_values(range, interval) {
         Ok(value) => value,
         Err(value) => return Err(value),
     };
 
+    let query = parse_query_arg(query)?;
+
+    run_timeline_pipeline(
+        start,
+        end,
+        interval,
+        min_percentage,
+        use_processes,
+        afk,
+        summary,
+        show_time,
+        query,
+        output,
+        timezone,
+        chart,
+    )
+    .await
+}
+
+/// Parses the `--query` flag shared by `timeline` and the `day`/`week`/`month`/`year` convenience
+/// commands, turning a parse failure into the same `clap` validation error both surface.
+fn parse_query_arg(query: Option<String>) -> Result<Option<QueryExpr>> {
+    match query.map(|q| parse_query(&q)) {
+        Some(Ok(query)) => Ok(Some(query)),
+        Some(Err(e)) => Err(Args::command()
+            .error(clap::error::ErrorKind::ValueValidation, format!("Invalid query: {e}"))
+            .into()),
+        None => Ok(None),
+    }
+}
+
+/// Shared tail end of `timeline` and the `day`/`week`/`month`/`year` convenience commands: turns a
+/// concrete range and interval into a stream of recorded intervals and dispatches to the right
+/// printer.
+#[allow(clippy::too_many_arguments)]
+async fn run_timeline_pipeline(
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    interval: SlidingInterval,
+    min_percentage: Percentage,
+    use_processes: bool,
+    afk: bool,
+    summary: bool,
+    show_time: bool,
+    query: Option<QueryExpr>,
+    output: OutputFormat,
+    timezone: TimezoneOption,
+    chart: ChartArgs,
+) -> Result<()> {
+    if output == OutputFormat::Chart && summary {
+        return Err(Args::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--output chart is not supported together with --summary, since a chart needs a timeline to plot",
+            )
+            .into());
+    }
+    if output == OutputFormat::Chart && !use_processes {
+        return Err(Args::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--output chart is only supported together with --processes",
+            )
+            .into());
+    }
+
     let application = RecordStorageImpl::new(create_application_default_path()?.join("records"))?;
 
     // We create a stream representing timeline between dates.
@@ -141,17 +332,171 @@ pub async fn process_timeline_command(
         output::ExtractConfig {
             start: start.into(),
             end: end.into(),
+            // `--summary` collects the whole range into a `Vec` before aggregating, so it doesn't
+            // care about chronological shard-opening order.
+            prefetch_order: if summary {
+                output::PrefetchOrder::Unordered
+            } else {
+                output::PrefetchOrder::Ordered
+            },
+            ..Default::default()
         },
     );
 
-    if use_processes {
-        print_processes_grouping(interval, min_percentage, afk, show_time, results).await?;
+    let results = results.filter_map(move |item| {
+        let keep = match (&item, &query) {
+            (Ok(entity), Some(query)) => query.matches(entity),
+            _ => true,
+        };
+        future::ready(keep.then_some(item))
+    });
+
+    if summary {
+        print_summary(min_percentage, afk, use_processes, output, results).await?;
+    } else if use_processes {
+        print_processes_grouping(interval, min_percentage, afk, show_time, output, timezone, chart, results)
+            .await?;
     } else {
-        print_window_grouping(interval, min_percentage, afk, show_time, results).await?;
+        print_window_grouping(interval, min_percentage, afk, show_time, output, timezone, results).await?;
     }
     Ok(())
 }
 
+/// A calendar granularity offered by the `day`/`week`/`month`/`year` convenience commands.
+#[derive(Debug, Clone, Copy)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Common flags shared by the `day`/`week`/`month`/`year` convenience commands. These skip
+/// [DateRangeArgs] entirely since the range is derived from `offset` instead of being parsed from
+/// free-form date strings.
+#[derive(Debug, Parser)]
+pub struct PeriodCommand {
+    #[arg(
+        default_value_t = 0,
+        allow_negative_numbers = true,
+        help = "Offset from the current period, e.g. -1 for last week, 2 for two months from now"
+    )]
+    offset: i64,
+    #[arg(short = 'p', long = "percentage", help = "Filter apps to have at least specified percentage", default_value_t = Percentage::new_opt(1.).unwrap()) ]
+    min_percentage: Percentage,
+    #[arg(short, long = "processes", help = "Ignore window names")]
+    use_processes: bool,
+    #[arg(
+        short,
+        long,
+        help = "Include time afk. Person is considered afk after 2 minutes of idle time."
+    )]
+    afk: bool,
+    #[arg(
+        long,
+        help = "Instead of a per-interval timeline, print one rolled-up total per process/window for the whole range"
+    )]
+    summary: bool,
+    #[arg(
+        long,
+        help = "Filter intervals before reporting, e.g. `process:firefox and not afk:true`. Predicates are process:VALUE, window:VALUE (case-insensitive substring match), afk:true/false and duration>30s, combined with and/or/not and parentheses"
+    )]
+    query: Option<String>,
+    #[arg(short = 'O', long = "output", default_value_t = OutputFormat::Table, help = "Output format")]
+    output: OutputFormat,
+    #[arg(long = "timezone", default_value_t = TimezoneOption::Local, help = "Timezone bucket boundaries (e.g. day/week starts) are computed against")]
+    timezone: TimezoneOption,
+    #[command(flatten)]
+    chart: ChartArgs,
+}
+
+/// Command to process the `day`/`week`/`month`/`year` convenience commands. Each computes its own
+/// `start`/`end` bounds using `DateTimeNow` and a sensible default [PrintInterval] for its
+/// granularity, then delegates into the same pipeline as `timeline`.
+pub async fn process_period_command(period: Period, command: PeriodCommand) -> Result<()> {
+    let PeriodCommand {
+        offset,
+        min_percentage,
+        use_processes,
+        afk,
+        summary,
+        query,
+        output,
+        timezone,
+        chart,
+    } = command;
+
+    let query = parse_query_arg(query)?;
+
+    let (start, end, interval) = match period {
+        Period::Day => {
+            let (start, end) = day_bounds(offset);
+            (start, end, SlidingInterval::new_opt(1, TimeOption::Hours).unwrap())
+        }
+        Period::Week => {
+            let (start, end) = week_bounds(offset);
+            (start, end, SlidingInterval::new_opt(1, TimeOption::Days).unwrap())
+        }
+        Period::Month => {
+            let (start, end) = month_bounds(offset);
+            (start, end, SlidingInterval::new_opt(1, TimeOption::Days).unwrap())
+        }
+        // TimeOption has no month-sized bucket, so a year is shown in week-sized buckets instead.
+        Period::Year => {
+            let (start, end) = year_bounds(offset);
+            (start, end, SlidingInterval::new_opt(1, TimeOption::Weeks).unwrap())
+        }
+    };
+
+    let show_time = *interval.time() > TimeOption::Days;
+
+    run_timeline_pipeline(
+        start,
+        end,
+        interval,
+        min_percentage,
+        use_processes,
+        afk,
+        summary,
+        show_time,
+        query,
+        output,
+        timezone,
+        chart,
+    )
+    .await
+}
+
+fn shift_months(base: DateTime<Local>, offset: i64) -> DateTime<Local> {
+    if offset >= 0 {
+        base.checked_add_months(Months::new(offset as u32))
+            .unwrap_or(base)
+    } else {
+        base.checked_sub_months(Months::new(offset.unsigned_abs() as u32))
+            .unwrap_or(base)
+    }
+}
+
+fn day_bounds(offset: i64) -> (DateTime<Local>, DateTime<Local>) {
+    let start = (Local::now() + Duration::days(offset)).beginning_of_day();
+    (start, start + Duration::days(1))
+}
+
+fn week_bounds(offset: i64) -> (DateTime<Local>, DateTime<Local>) {
+    let start = (Local::now() + Duration::weeks(offset)).beginning_of_week();
+    (start, start + Duration::weeks(1))
+}
+
+fn month_bounds(offset: i64) -> (DateTime<Local>, DateTime<Local>) {
+    let start = shift_months(Local::now(), offset).beginning_of_month();
+    (start, shift_months(start, 1))
+}
+
+fn year_bounds(offset: i64) -> (DateTime<Local>, DateTime<Local>) {
+    let start = shift_months(Local::now(), offset * 12).beginning_of_year();
+    (start, shift_months(start, 12))
+}
+
 struct ParamParseResult {
     interval: SlidingInterval,
     start: DateTime<Local>,
@@ -160,28 +505,34 @@ struct ParamParseResult {
 }
 
 /// Also provides sensible defaults for `timeline` command.
-fn parse_values(
-    start_date: Option<String>,
-    end_date: Option<String>,
-    date_style: DateStyle,
-    interval: PrintInterval,
+fn parse_values(range: DateRangeArgs, interval: PrintInterval) -> Result<ParamParseResult> {
+    let interval = interval.resolve()?;
+    let treat_as_days = range.treat_as_days || *interval.time() <= TimeOption::Days;
+
+    let default_start = Local::now() - interval.as_duration() * DEFAULT_PRINTED_INTERVALS;
+    let (start, end) = parse_date_range(range, treat_as_days, default_start)?;
+
+    let show_time = *interval.time() > TimeOption::Days;
+
+    Ok(ParamParseResult {
+        interval,
+        start,
+        end,
+        show_time,
+    })
+}
+
+/// Parses a [DateRangeArgs] into a concrete `(start, end)` range, applying `default_start` when
+/// `--start` wasn't given and rounding to whole days when `treat_as_days` is set. Shared between
+/// `timeline` and `export influx` so both commands understand the same date syntax.
+pub(crate) fn parse_date_range(
+    range: DateRangeArgs,
     treat_as_days: bool,
-) -> Result<ParamParseResult> {
-    let treat_as_days = treat_as_days || interval.option <= TimeOption::Days;
-    let Some(interval) = SlidingInterval::new_opt(interval.duration, interval.option) else {
-        return Err(Args::command()
-            .error(
-                clap::error::ErrorKind::ValueValidation,
-                format!(
-                    "Can't create an interval using {} and {}",
-                    interval.duration, interval.option
-                ),
-            )
-            .into());
-    };
+    default_start: DateTime<Local>,
+) -> Result<(DateTime<Local>, DateTime<Local>)> {
     let now = Local::now();
-    let dialect: chrono_english::Dialect = date_style.into();
-    let mut start = match start_date.map(|s| parse_date_string(&s, now, dialect)) {
+    let dialect: chrono_english::Dialect = range.date_style.into();
+    let mut start = match range.start_date.map(|s| parse_date_string(&s, now, dialect)) {
         Some(Ok(v)) => v.with_timezone(&Local),
         Some(Err(e)) => {
             return Err(Args::command()
@@ -191,9 +542,9 @@ fn parse_values(
                 )
                 .into());
         }
-        None => Local::now() - interval.as_duration() * DEFAULT_PRINTED_INTERVALS,
+        None => default_start,
     };
-    let mut end = match end_date.map(|s| parse_date_string(&s, now, dialect)) {
+    let mut end = match range.end_date.map(|s| parse_date_string(&s, now, dialect)) {
         Some(Ok(v)) => v.with_timezone(&Local),
         Some(Err(e)) => {
             return Err(Args::command()
@@ -210,51 +561,88 @@ fn parse_values(
         end = next_day_start(end);
     }
 
-    let show_time = *interval.time() > TimeOption::Days;
-
-    Ok(ParamParseResult {
-        interval,
-        start,
-        end,
-        show_time,
-    })
+    Ok((start, end))
 }
 
 // Realistically print_processes_grouping and print_window_grouping can be combined, however this
 // will require abstractions for just 80 lines of code.
 
+/// `sliding_interval_grouping` picks its bucket boundaries at compile time via its `Tz` type
+/// parameter, so a runtime `--timezone` choice has to be resolved into one of a fixed set of
+/// monomorphizations rather than passed as a value.
 async fn print_processes_grouping(
     interval: SlidingInterval,
     min_percentage: Percentage,
     afk: bool,
     show_time: bool,
+    output: OutputFormat,
+    timezone: TimezoneOption,
+    chart: ChartArgs,
     results: impl Stream<Item = std::result::Result<UsageIntervalEntity, anyhow::Error>>,
 ) -> Result<()> {
-    let intervals = sliding_interval_grouping::<_, Local>(results, interval, |v| {
+    match timezone {
+        TimezoneOption::Local => {
+            print_processes_grouping_in::<Local>(interval, min_percentage, afk, show_time, output, chart, results)
+                .await
+        }
+        TimezoneOption::Utc => {
+            print_processes_grouping_in::<Utc>(interval, min_percentage, afk, show_time, output, chart, results).await
+        }
+    }
+}
+
+async fn print_processes_grouping_in<Tz: TimeZone>(
+    interval: SlidingInterval,
+    min_percentage: Percentage,
+    afk: bool,
+    show_time: bool,
+    output: OutputFormat,
+    chart: ChartArgs,
+    results: impl Stream<Item = std::result::Result<UsageIntervalEntity, anyhow::Error>>,
+) -> Result<()>
+where
+    DateTime<Tz>: From<DateTime<Utc>>,
+    Tz::Offset: Display,
+{
+    let intervals = sliding_interval_grouping::<_, Tz>(results, interval, |v| {
         analyze_processes(v, min_percentage, afk)
     })
     .await?;
+
+    let time_format = if show_time { "%x %H:%M:%S" } else { "%x" };
+
+    if output == OutputFormat::Chart {
+        let rows = intervals
+            .into_iter()
+            .filter_map(|(time, value)| {
+                let (analyzed, computer_on_duration) = value?;
+                if analyzed.is_empty() {
+                    return None;
+                }
+                let time = DateTime::<Tz>::from(time).format(time_format).to_string();
+                Some((time, analyzed, computer_on_duration))
+            })
+            .collect();
+
+        render_process_timeline(chart.style, chart.top, chart.width, rows);
+        return Ok(());
+    }
+
+    let mut formatter = make_formatter(output);
+
     for (time, value) in intervals {
         let Some((analyzed, computer_on_duration)) = value else {
             continue;
         };
+        if analyzed.is_empty() {
+            continue;
+        }
 
-        let time = time.with_timezone(&Local);
-
-        let time_format = if show_time { "%x %H:%M:%S" } else { "%x" };
-
-        if !analyzed.is_empty() {
-            for entry in analyzed {
-                println!(
-                    "{}\t{}%\t{}\t{}",
-                    time.format(time_format),
-                    *duration_percentage(entry.duration, computer_on_duration) as i32,
-                    format_duration(entry.duration),
-                    clean_process_name(&entry.process_name)
-                );
-            }
-            println!();
+        let time = DateTime::<Tz>::from(time).format(time_format).to_string();
+        for entry in &analyzed {
+            formatter.write_row(&process_row(Some(time.clone()), entry, computer_on_duration));
         }
+        formatter.end_group();
     }
     Ok(())
 }
@@ -264,39 +652,213 @@ async fn print_window_grouping(
     min_percentage: Percentage,
     afk: bool,
     show_time: bool,
+    output: OutputFormat,
+    timezone: TimezoneOption,
     results: impl Stream<Item = std::result::Result<UsageIntervalEntity, anyhow::Error>>,
 ) -> Result<()> {
-    let intervals = sliding_interval_grouping::<_, Local>(results, interval, |v| {
+    match timezone {
+        TimezoneOption::Local => {
+            print_window_grouping_in::<Local>(interval, min_percentage, afk, show_time, output, results).await
+        }
+        TimezoneOption::Utc => {
+            print_window_grouping_in::<Utc>(interval, min_percentage, afk, show_time, output, results).await
+        }
+    }
+}
+
+async fn print_window_grouping_in<Tz: TimeZone>(
+    interval: SlidingInterval,
+    min_percentage: Percentage,
+    afk: bool,
+    show_time: bool,
+    output: OutputFormat,
+    results: impl Stream<Item = std::result::Result<UsageIntervalEntity, anyhow::Error>>,
+) -> Result<()>
+where
+    DateTime<Tz>: From<DateTime<Utc>>,
+    Tz::Offset: Display,
+{
+    let intervals = sliding_interval_grouping::<_, Tz>(results, interval, |v| {
         analyze_windows(v, min_percentage, afk)
     })
     .await?;
+
+    let mut formatter = make_formatter(output);
+    let time_format = if show_time { "%x %H:%M:%S" } else { "%x" };
+
     for (time, value) in intervals {
         let Some((analyzed, computer_on_duration)) = value else {
             continue;
         };
+        if analyzed.is_empty() {
+            continue;
+        }
+
+        let time = DateTime::<Tz>::from(time).format(time_format).to_string();
+        for entry in &analyzed {
+            formatter.write_row(&window_row(Some(time.clone()), entry, computer_on_duration));
+        }
+        formatter.end_group();
+    }
+    Ok(())
+}
 
-        let time = time.with_timezone(&Local);
+/// Instead of bucketing by [SlidingInterval], folds the whole range into one rolled-up total per
+/// process/window, printed sorted descending by duration, with a trailing footer summarizing the
+/// whole range. The footer is table-only, since json/csv consumers can compute totals themselves.
+async fn print_summary(
+    min_percentage: Percentage,
+    afk: bool,
+    use_processes: bool,
+    output: OutputFormat,
+    results: impl Stream<Item = std::result::Result<UsageIntervalEntity, anyhow::Error>>,
+) -> Result<()> {
+    let intervals: Vec<UsageIntervalEntity> = results.try_collect().await?;
 
-        let time_format = if show_time { "%x %H:%M:%S" } else { "%x" };
+    let total_duration = intervals.iter().fold(Duration::zero(), |acc, v| acc + v.duration);
+    let afk_duration = intervals
+        .iter()
+        .filter(|v| v.afk)
+        .fold(Duration::zero(), |acc, v| acc + v.duration);
 
-        if !analyzed.is_empty() {
-            for entry in analyzed {
-                println!(
-                    "{}\t{}%\t{}\t{}\t{}",
-                    time.format(time_format),
-                    *duration_percentage(entry.duration, computer_on_duration) as i32,
-                    format_duration(entry.duration),
-                    clean_process_name(&entry.process_name),
-                    entry.window_name
-                );
-            }
-            println!();
+    let mut formatter = make_formatter(output);
+
+    let distinct_apps = if use_processes {
+        let (analyzed, computer_on_duration) = analyze_processes(intervals, min_percentage, afk);
+        for entry in &analyzed {
+            formatter.write_row(&process_row(None, entry, computer_on_duration));
+        }
+        analyzed.len()
+    } else {
+        let (analyzed, computer_on_duration) = analyze_windows(intervals, min_percentage, afk);
+        for entry in &analyzed {
+            formatter.write_row(&window_row(None, entry, computer_on_duration));
         }
+        analyzed.len()
+    };
+
+    if output == OutputFormat::Table {
+        println!();
+        println!(
+            "Total tracked: {}\tTotal AFK: {}\tDistinct apps: {}",
+            format_duration(total_duration),
+            format_duration(afk_duration),
+            distinct_apps
+        );
     }
+
     Ok(())
 }
 
-fn format_duration(v: Duration) -> String {
+/// One formatted line of timeline output: an analyzed process/window entry attributed to a bucket
+/// (or to the whole range, for `--summary`).
+#[derive(Debug, Clone, Serialize)]
+struct ReportRow {
+    time: Option<String>,
+    percentage: i32,
+    duration_seconds: i64,
+    process_name: String,
+    window_name: Option<String>,
+    afk: bool,
+}
+
+fn process_row(time: Option<String>, entry: &ProcessUsage, computer_on_duration: Duration) -> ReportRow {
+    ReportRow {
+        time,
+        percentage: *duration_percentage(entry.duration, computer_on_duration) as i32,
+        duration_seconds: entry.duration.num_seconds(),
+        process_name: clean_process_name(&entry.process_name),
+        window_name: None,
+        afk: entry.process_name.as_ref() == "Inactive",
+    }
+}
+
+fn window_row(time: Option<String>, entry: &WindowUsage, computer_on_duration: Duration) -> ReportRow {
+    ReportRow {
+        time,
+        percentage: *duration_percentage(entry.duration, computer_on_duration) as i32,
+        duration_seconds: entry.duration.num_seconds(),
+        process_name: clean_process_name(&entry.process_name),
+        window_name: Some(entry.window_name.to_string()),
+        afk: entry.process_name.as_ref() == "Inactive",
+    }
+}
+
+/// Shared by the `table`/`json`/`csv` output formats so the printers above don't each special-case
+/// how a row gets written. `table` groups rows into one paragraph per bucket; `json`/`csv` are flat.
+trait RowFormatter {
+    fn write_row(&mut self, row: &ReportRow);
+    fn end_group(&mut self) {}
+}
+
+struct TableFormatter;
+
+impl RowFormatter for TableFormatter {
+    fn write_row(&mut self, row: &ReportRow) {
+        let duration = format_duration(Duration::seconds(row.duration_seconds));
+        match (&row.time, &row.window_name) {
+            (Some(time), Some(window_name)) => {
+                println!("{time}\t{}%\t{duration}\t{}\t{window_name}", row.percentage, row.process_name)
+            }
+            (Some(time), None) => println!("{time}\t{}%\t{duration}\t{}", row.percentage, row.process_name),
+            (None, Some(window_name)) => {
+                println!("{}%\t{duration}\t{}\t{window_name}", row.percentage, row.process_name)
+            }
+            (None, None) => println!("{}%\t{duration}\t{}", row.percentage, row.process_name),
+        }
+    }
+
+    fn end_group(&mut self) {
+        println!();
+    }
+}
+
+struct JsonFormatter;
+
+impl RowFormatter for JsonFormatter {
+    fn write_row(&mut self, row: &ReportRow) {
+        match serde_json::to_string(row) {
+            Ok(line) => println!("{line}"),
+            Err(e) => eprintln!("Failed to serialize row as json: {e}"),
+        }
+    }
+}
+
+struct CsvFormatter {
+    header_written: bool,
+}
+
+impl RowFormatter for CsvFormatter {
+    fn write_row(&mut self, row: &ReportRow) {
+        if !self.header_written {
+            println!("time,percentage,duration_seconds,process_name,window_name,afk");
+            self.header_written = true;
+        }
+        println!(
+            "{},{},{},{},{},{}",
+            row.time.as_deref().unwrap_or_default(),
+            row.percentage,
+            row.duration_seconds,
+            csv::escape(&row.process_name),
+            row.window_name.as_deref().map(csv::escape).unwrap_or_default(),
+            row.afk,
+        );
+    }
+}
+
+/// Callers must route `OutputFormat::Chart` to [render_process_timeline] instead; it doesn't fit
+/// the row-at-a-time [RowFormatter] model, since a chart needs every bucket at once to align all
+/// time labels to the widest one.
+fn make_formatter(output: OutputFormat) -> Box<dyn RowFormatter> {
+    match output {
+        OutputFormat::Table => Box::new(TableFormatter),
+        OutputFormat::Json => Box::new(JsonFormatter),
+        OutputFormat::Csv => Box::new(CsvFormatter { header_written: false }),
+        OutputFormat::Chart => unreachable!("--output chart is routed to render_process_timeline before reaching make_formatter"),
+    }
+}
+
+pub(crate) fn format_duration(v: Duration) -> String {
     if v.num_hours() > 0 {
         format!(
             "{}h{}m{}s",
@@ -311,7 +873,7 @@ fn format_duration(v: Duration) -> String {
     }
 }
 
-fn clean_process_name(value: &str) -> String {
+pub(crate) fn clean_process_name(value: &str) -> String {
     PathBuf::from(value)
         .file_name()
         .map(|v| v.to_string_lossy().to_string())
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use clap::Parser;
 use tracing::level_filters::LevelFilter;
 
+use super::notify::BudgetRule;
 
 #[derive(Parser)]
 pub struct DaemonArgs {
@@ -14,5 +15,19 @@ pub struct DaemonArgs {
   #[arg(long = "log-console")]
   pub log_console : bool,
   #[arg(long = "log-filter")]
-  pub log: Option<LevelFilter>
+  pub log: Option<LevelFilter>,
+  #[arg(long = "notify", help = "Show desktop notifications for AFK transitions and exceeded usage budgets")]
+  pub notify: bool,
+  #[arg(long = "notify-budget", help = "Per-process daily usage budget, e.g. `firefox=2h`. Can be repeated.")]
+  pub notify_budget: Vec<BudgetRule>,
+  #[arg(long = "collection-interval-secs", default_value_t = 1, help = "How often the collector samples the active window, in seconds. Reloadable via SIGHUP.")]
+  pub collection_interval_secs: u64,
+  #[arg(long = "afk-threshold-secs", default_value_t = 120, help = "Idle time after which the user is considered AFK, in seconds. Reloadable via SIGHUP.")]
+  pub afk_threshold_secs: u32,
+  #[arg(long = "object-store-url", help = "Store records through an object_store URL (e.g. s3://bucket/prefix) instead of the local filesystem, so activity logs can be centralized/synced across machines")]
+  pub object_store_url: Option<String>,
+  #[arg(long = "retention-max-age-days", help = "Delete record files older than this many days. Defaults to 365; pass 0 to keep everything")]
+  pub retention_max_age_days: Option<u32>,
+  #[arg(long = "retention-max-bytes", help = "Delete the oldest record files until the record directory is at or under this many bytes. Unset by default (no size cap)")]
+  pub retention_max_bytes: Option<u64>,
 }
@@ -1,23 +1,42 @@
 use std::time::Duration;
 
 use anyhow::Result;
-use tokio::sync::mpsc;
+use futures::Stream;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_stream::{StreamExt as _, wrappers::BroadcastStream};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, info_span, Instrument};
 
 use crate::{
-    daemon::storage::record_event::RecordEvent, utils::clock::Clock, window_api::WindowManager,
+    daemon::{notify::Notifier, storage::record_event::RecordEvent},
+    utils::clock::Clock,
+    window_api::WindowManager,
 };
 
 use super::afk::AfkEvaluator;
 
+/// How many events a lagging live subscriber can fall behind by before it starts dropping the
+/// oldest ones. Durable storage is unaffected either way, since it goes through `next` instead.
+const LIVE_SUBSCRIPTION_CAPACITY: usize = 64;
+
+/// The subset of the collector's behavior that a config reload (SIGHUP) can change at runtime.
+/// Held in a [watch] channel so [DataCollectionModule::run] picks up a new value on its next loop
+/// iteration without needing to be torn down and recreated.
+#[derive(Debug, Clone, Copy)]
+pub struct CollectorSettings {
+    pub collection_frequency: Duration,
+    pub afk_threshold_s: u32,
+}
+
 pub struct DataCollectionModule {
     next: mpsc::Sender<RecordEvent>,
+    live: broadcast::Sender<RecordEvent>,
     producer: Box<dyn WindowManager>,
     shutdown: CancellationToken,
-    afk_evaluator: AfkEvaluator,
-    collection_frequency: Duration,
+    settings: watch::Receiver<CollectorSettings>,
+    paused: watch::Receiver<bool>,
     time_provider: Box<dyn Clock>,
+    notifier: Notifier,
 }
 
 impl DataCollectionModule {
@@ -25,24 +44,36 @@ impl DataCollectionModule {
         next: mpsc::Sender<RecordEvent>,
         producer: Box<dyn WindowManager>,
         shutdown: CancellationToken,
-        afk_evaluator: AfkEvaluator,
-        collection_frequency: Duration,
+        settings: watch::Receiver<CollectorSettings>,
+        paused: watch::Receiver<bool>,
         time_provider: Box<dyn Clock>,
+        notifier: Notifier,
     ) -> Self {
+        let (live, _) = broadcast::channel(LIVE_SUBSCRIPTION_CAPACITY);
         Self {
             next,
+            live,
             producer,
-            collection_frequency,
-            afk_evaluator,
+            settings,
+            paused,
             time_provider,
             shutdown,
+            notifier,
         }
     }
 
-    fn collect_data(&mut self) -> Result<RecordEvent> {
+    /// Subscribes to every event collected from now on, for consumers like a status bar or live
+    /// dashboard that want to tail the current window/afk state without reading storage files.
+    /// This is best-effort: a subscriber that falls behind drops the oldest events it missed
+    /// instead of ever blocking the collection loop.
+    pub fn subscribe(&self) -> impl Stream<Item = RecordEvent> {
+        BroadcastStream::new(self.live.subscribe()).filter_map(|result| result.ok())
+    }
+
+    fn collect_data(&mut self, afk_threshold_s: u32) -> Result<RecordEvent> {
         let window_data = self.producer.get_active_window_data()?;
         let idle_ms = self.producer.get_idle_time()?;
-        let afk = self.afk_evaluator.is_afk(idle_ms);
+        let afk = AfkEvaluator::from_seconds(afk_threshold_s).is_afk(idle_ms);
         let timestamp = self.time_provider.time();
 
         Ok(RecordEvent {
@@ -53,25 +84,42 @@ impl DataCollectionModule {
         })
     }
 
-    /// Executes the collector event loop.
+    /// Executes the collector event loop. Re-reads [CollectorSettings] at the top of every
+    /// iteration, so a config reload's new collection frequency/AFK threshold takes effect on the
+    /// very next tick without needing to restart the loop. While `paused` (set by the control
+    /// socket's `pause` command) the tick still fires, it just skips collecting and sending a
+    /// record, so `resume` picks back up without restarting the loop or its clock.
     pub async fn run(mut self) -> Result<()> {
         let mut collection_point = self.time_provider.instant();
         loop {
-            collection_point += self.collection_frequency;
+            let settings = *self.settings.borrow_and_update();
+            let paused = *self.paused.borrow_and_update();
+            collection_point += settings.collection_frequency;
 
-            match self.collect_data() {
-                Ok(record) => {
-                    let span = info_span!("Processing collected data");
-                    debug!("Sending message {:?}", record);
-                    self.next
-                        .send(record)
-                        .instrument(span)
-                        .await
-                        .inspect_err(|e| error!("Unexpected error during sending {e:?}"))?;
-                    info!("Successfully sent message")
-                }
-                Err(e) => {
-                    error!("Encountered an error during collection {:?}", e)
+            if paused {
+                debug!("Collection paused, skipping tick");
+            } else {
+                match self.collect_data(settings.afk_threshold_s) {
+                    Ok(record) => {
+                        // Best-effort: no subscribers is the common case and not an error.
+                        let _ = self.live.send(record.clone());
+
+                        if let Ok(tick) = chrono::Duration::from_std(settings.collection_frequency) {
+                            self.notifier.observe(&record, tick);
+                        }
+
+                        let span = info_span!("Processing collected data");
+                        debug!("Sending message {:?}", record);
+                        self.next
+                            .send(record)
+                            .instrument(span)
+                            .await
+                            .inspect_err(|e| error!("Unexpected error during sending {e:?}"))?;
+                        info!("Successfully sent message")
+                    }
+                    Err(e) => {
+                        error!("Encountered an error during collection {:?}", e)
+                    }
                 }
             }
 
@@ -0,0 +1,38 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// The subset of [DaemonArgs](super::args::DaemonArgs) that can be changed on a running daemon
+/// without a restart. Persisted under the app directory so a reload (SIGHUP or the control
+/// socket's `Reload` command) has an actual source of fresh values to read, instead of re-parsing
+/// the same argv the process started with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ReloadableConfig {
+    pub collection_interval_secs: u64,
+    pub afk_threshold_secs: u32,
+}
+
+pub fn config_path(app_dir: &Path) -> PathBuf {
+    app_dir.join("config.json")
+}
+
+/// Writes `config` to `app_dir`'s config file, creating or overwriting it. Called once at daemon
+/// startup with the values parsed from argv, and again whenever the control socket's `Reload`
+/// command carries new values, so the file always reflects whatever the daemon is currently
+/// running with.
+pub fn write_config(app_dir: &Path, config: ReloadableConfig) -> Result<()> {
+    let path = config_path(app_dir);
+    let json = serde_json::to_string_pretty(&config)?;
+    std::fs::write(&path, json).with_context(|| format!("Failed to write config to {path:?}"))
+}
+
+/// Reads back whatever [write_config] last wrote. Used on every reload so a SIGHUP with no
+/// control-socket payload still picks up the most recently applied settings instead of reverting
+/// to the process's original argv.
+pub fn read_config(app_dir: &Path) -> Result<ReloadableConfig> {
+    let path = config_path(app_dir);
+    let json = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config from {path:?}"))?;
+    serde_json::from_str(&json).with_context(|| format!("Failed to parse config at {path:?}"))
+}
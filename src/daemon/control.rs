@@ -0,0 +1,390 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::{mpsc, oneshot, watch},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use super::{
+    collection::collector::CollectorSettings,
+    config::{self, ReloadableConfig},
+    processing::request_flush,
+    status::StatusState,
+    storage::record_storage::{RecordStorage, RecordStorageImpl},
+};
+
+/// A command sent to a running daemon over its control socket, one JSON object per line. Lets the
+/// CLI change the daemon's behavior at runtime instead of having to stop and re-spawn it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ControlRequest {
+    /// Reports uptime, whether collection is paused, the active window, and how many records
+    /// (stored intervals) today's record file holds.
+    Status,
+    /// Forces the current record file to flush without waiting for the daemon to shut down.
+    Flush,
+    /// Suspends event emission from the collector, without stopping its loop or clock.
+    Pause,
+    /// Re-enables event emission suspended by [ControlRequest::Pause].
+    Resume,
+    /// Re-reads the daemon's configuration, same as sending it a SIGHUP. When `new_settings`
+    /// fields are set, they're applied immediately and persisted to the config file before the
+    /// reload happens, instead of just re-triggering a read of whatever was there already.
+    Reload { new_settings: ReloadSettingsPatch },
+}
+
+/// Fields of [ReloadableConfig] the control socket's `Reload` command can override. Unset fields
+/// keep whatever the daemon is already running with.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ReloadSettingsPatch {
+    pub collection_interval_secs: Option<u64>,
+    pub afk_threshold_secs: Option<u32>,
+}
+
+/// The daemon's reply to a [ControlRequest], one JSON object per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ControlResponse {
+    Status {
+        uptime_secs: i64,
+        paused: bool,
+        active_window: Option<String>,
+        events_today: usize,
+    },
+    Ok,
+    Error(String),
+}
+
+/// Where the daemon's Unix domain socket lives, so the CLI can find a running daemon from its app
+/// directory instead of scanning processes via `find_servers`.
+#[cfg(unix)]
+pub fn control_socket_path(app_dir: &Path) -> PathBuf {
+    app_dir.join("control.sock")
+}
+
+/// The name of the daemon's named pipe, derived from `app_dir` since pipe names aren't paths on
+/// Windows. Hashing keeps this stable for a given app directory without worrying about characters
+/// a pipe name can't contain.
+#[cfg(windows)]
+pub fn control_pipe_name(app_dir: &Path) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    app_dir.hash(&mut hasher);
+    format!(r"\\.\pipe\whatawhat-control-{:x}", hasher.finish())
+}
+
+/// Everything [run_control_server] needs to answer a [ControlRequest]. Built from the pieces
+/// [super::start_daemon] already wires up for the collector/processor, rather than constructed
+/// fresh for the control server.
+pub struct ControlContext {
+    pub status: Arc<StatusState>,
+    pub started_at: DateTime<Utc>,
+    pub record_dir: PathBuf,
+    pub app_dir: PathBuf,
+    pub paused: watch::Sender<bool>,
+    pub settings: watch::Sender<CollectorSettings>,
+    pub flush: mpsc::Sender<oneshot::Sender<Result<()>>>,
+}
+
+/// Accepts control connections under `app_dir` until `shutdown` is cancelled, answering each
+/// [ControlRequest] line read from the connection with a [ControlResponse] line in turn.
+pub async fn run_control_server(
+    app_dir: PathBuf,
+    ctx: ControlContext,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let ctx = Arc::new(ctx);
+
+    #[cfg(unix)]
+    {
+        let path = control_socket_path(&app_dir);
+        // Best-effort: clears a stale socket left behind by an unclean previous exit.
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path)
+            .with_context(|| format!("Failed to bind control socket at {path:?}"))?;
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted.context("Failed to accept control connection")?;
+                    let ctx = ctx.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, &ctx).await {
+                            warn!("Control connection error: {e:?}");
+                        }
+                    });
+                }
+            }
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+    #[cfg(windows)]
+    {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let pipe_name = control_pipe_name(&app_dir);
+
+        loop {
+            let server = ServerOptions::new()
+                .first_pipe_instance(false)
+                .create(&pipe_name)
+                .with_context(|| format!("Failed to create control pipe {pipe_name}"))?;
+
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                connected = server.connect() => {
+                    connected.context("Failed to accept control connection")?;
+                    let ctx = ctx.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(server, &ctx).await {
+                            warn!("Control connection error: {e:?}");
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_connection<S>(stream: S, ctx: &ControlContext) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .context("Failed to read control request")?
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => dispatch(ctx, request).await,
+            Err(e) => ControlResponse::Error(format!("Malformed control request: {e}")),
+        };
+
+        let mut payload = serde_json::to_vec(&response)?;
+        payload.push(b'\n');
+        writer
+            .write_all(&payload)
+            .await
+            .context("Failed to write control response")?;
+    }
+
+    Ok(())
+}
+
+async fn dispatch(ctx: &ControlContext, request: ControlRequest) -> ControlResponse {
+    match request {
+        ControlRequest::Status => match status_snapshot(ctx).await {
+            Ok(response) => response,
+            Err(e) => ControlResponse::Error(format!("{e:#}")),
+        },
+        ControlRequest::Flush => match request_flush(&ctx.flush).await {
+            Ok(()) => ControlResponse::Ok,
+            Err(e) => ControlResponse::Error(format!("{e:#}")),
+        },
+        ControlRequest::Pause => {
+            info!("Pausing collection via control socket");
+            let _ = ctx.paused.send(true);
+            ControlResponse::Ok
+        }
+        ControlRequest::Resume => {
+            info!("Resuming collection via control socket");
+            let _ = ctx.paused.send(false);
+            ControlResponse::Ok
+        }
+        ControlRequest::Reload { new_settings } => match apply_reload(ctx, new_settings) {
+            Ok(()) => ControlResponse::Ok,
+            Err(e) => ControlResponse::Error(format!("{e:#}")),
+        },
+    }
+}
+
+/// Merges `patch` onto the currently running [CollectorSettings], persists the result to the
+/// config file (so a later bare SIGHUP picks it up too), and pushes it directly to the collector.
+fn apply_reload(ctx: &ControlContext, patch: ReloadSettingsPatch) -> Result<()> {
+    info!("Reloading configuration via control socket");
+    let current = *ctx.settings.borrow();
+    let updated = CollectorSettings {
+        collection_frequency: patch
+            .collection_interval_secs
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(current.collection_frequency),
+        afk_threshold_s: patch.afk_threshold_secs.unwrap_or(current.afk_threshold_s),
+    };
+
+    config::write_config(
+        &ctx.app_dir,
+        ReloadableConfig {
+            collection_interval_secs: updated.collection_frequency.as_secs(),
+            afk_threshold_secs: updated.afk_threshold_s,
+        },
+    )?;
+
+    ctx.settings
+        .send(updated)
+        .map_err(|_| anyhow::anyhow!("Collector is not running"))
+}
+
+async fn status_snapshot(ctx: &ControlContext) -> Result<ControlResponse> {
+    let storage = RecordStorageImpl::new(ctx.record_dir.clone())?;
+    let today = Utc::now().date_naive();
+    let events_today = storage.get_data_for(today).await?.len();
+
+    Ok(ControlResponse::Status {
+        uptime_secs: (Utc::now() - ctx.started_at).num_seconds(),
+        paused: *ctx.paused.borrow(),
+        active_window: ctx.status.active_window(),
+        events_today,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn test_context(
+        app_dir: &Path,
+    ) -> (
+        ControlContext,
+        watch::Receiver<bool>,
+        watch::Receiver<CollectorSettings>,
+        mpsc::Receiver<oneshot::Sender<Result<()>>>,
+    ) {
+        let (paused_sender, paused_receiver) = watch::channel(false);
+        let (settings_sender, settings_receiver) = watch::channel(CollectorSettings {
+            collection_frequency: StdDuration::from_secs(1),
+            afk_threshold_s: 120,
+        });
+        let (flush_sender, flush_receiver) = mpsc::channel(1);
+
+        let ctx = ControlContext {
+            status: StatusState::new(),
+            started_at: Utc::now(),
+            record_dir: app_dir.join("records"),
+            app_dir: app_dir.to_path_buf(),
+            paused: paused_sender,
+            settings: settings_sender,
+            flush: flush_sender,
+        };
+
+        (ctx, paused_receiver, settings_receiver, flush_receiver)
+    }
+
+    #[tokio::test]
+    async fn dispatch_status_reports_paused_state_and_zero_events() {
+        let dir = tempdir().unwrap();
+        let (ctx, ..) = test_context(dir.path());
+
+        match dispatch(&ctx, ControlRequest::Status).await {
+            ControlResponse::Status { paused, events_today, .. } => {
+                assert!(!paused);
+                assert_eq!(events_today, 0);
+            }
+            other => panic!("expected Status, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch_pause_then_resume_toggles_the_paused_watch() {
+        let dir = tempdir().unwrap();
+        let (ctx, mut paused_receiver, ..) = test_context(dir.path());
+
+        assert!(matches!(dispatch(&ctx, ControlRequest::Pause).await, ControlResponse::Ok));
+        paused_receiver.changed().await.unwrap();
+        assert!(*paused_receiver.borrow());
+
+        assert!(matches!(dispatch(&ctx, ControlRequest::Resume).await, ControlResponse::Ok));
+        paused_receiver.changed().await.unwrap();
+        assert!(!*paused_receiver.borrow());
+    }
+
+    #[tokio::test]
+    async fn dispatch_flush_forwards_the_request_and_returns_ok() {
+        let dir = tempdir().unwrap();
+        let (ctx, _, _, mut flush_receiver) = test_context(dir.path());
+
+        tokio::spawn(async move {
+            let reply = flush_receiver.recv().await.unwrap();
+            let _ = reply.send(Ok(()));
+        });
+
+        assert!(matches!(dispatch(&ctx, ControlRequest::Flush).await, ControlResponse::Ok));
+    }
+
+    #[tokio::test]
+    async fn dispatch_reload_applies_patch_and_persists_config() {
+        let dir = tempdir().unwrap();
+        let (ctx, _, mut settings_receiver, _) = test_context(dir.path());
+
+        let response = dispatch(
+            &ctx,
+            ControlRequest::Reload {
+                new_settings: ReloadSettingsPatch {
+                    collection_interval_secs: Some(5),
+                    afk_threshold_secs: None,
+                },
+            },
+        )
+        .await;
+        assert!(matches!(response, ControlResponse::Ok));
+
+        settings_receiver.changed().await.unwrap();
+        let updated = *settings_receiver.borrow();
+        assert_eq!(updated.collection_frequency, StdDuration::from_secs(5));
+        assert_eq!(updated.afk_threshold_s, 120);
+
+        let persisted = config::read_config(dir.path()).unwrap();
+        assert_eq!(persisted.collection_interval_secs, 5);
+    }
+
+    /// `handle_connection` needs no real socket/pipe to exercise, since it's already generic over
+    /// `AsyncRead + AsyncWrite`; an in-memory duplex stream is enough to round-trip a request.
+    #[tokio::test]
+    async fn handle_connection_round_trips_a_status_request_over_an_in_memory_duplex() {
+        let dir = tempdir().unwrap();
+        let (ctx, ..) = test_context(dir.path());
+        let ctx = Arc::new(ctx);
+
+        let (client, server) = tokio::io::duplex(1024);
+        let server_task = tokio::spawn({
+            let ctx = ctx.clone();
+            async move { handle_connection(server, &ctx).await }
+        });
+
+        let (read_half, mut write_half) = tokio::io::split(client);
+        write_half
+            .write_all(serde_json::to_string(&ControlRequest::Status).unwrap().as_bytes())
+            .await
+            .unwrap();
+        write_half.write_all(b"\n").await.unwrap();
+        write_half.shutdown().await.unwrap();
+
+        let mut lines = BufReader::new(read_half).lines();
+        let line = lines.next_line().await.unwrap().expect("expected a response line");
+        let response: ControlResponse = serde_json::from_str(&line).unwrap();
+        assert!(matches!(response, ControlResponse::Status { .. }));
+
+        server_task.await.unwrap().unwrap();
+    }
+}
@@ -1,12 +1,22 @@
 use std::{path::PathBuf, time::Duration};
 
 use anyhow::Result;
-use collection::{afk::AfkEvaluator, collector::DataCollectionModule};
+use chrono::{Duration as ChronoDuration, Utc};
+use collection::collector::{CollectorSettings, DataCollectionModule};
+use config::ReloadableConfig;
+use control::ControlContext;
+use notify::{BudgetRule, Notifier};
+use pid_file::{remove_pid_file, write_pid_file};
 use processing::{local_save::LocalSaver, ProcessingModule};
-use storage::{record_event::RecordEvent, record_storage::RecordStorageImpl};
-use tokio::sync::mpsc;
+use status::{run_status_reporter, status_file_path, StatusState};
+use storage::{
+    object_store_storage::RecordStorageBackend,
+    record_event::RecordEvent,
+    record_storage::{RecordStorageImpl, RetentionPolicy},
+};
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio_util::sync::CancellationToken;
-use tracing::error;
+use tracing::{error, info};
 
 use crate::{
     utils::clock::{Clock, DefaultClock},
@@ -15,27 +25,118 @@ use crate::{
 
 pub mod args;
 pub mod collection;
+pub mod config;
+pub mod control;
+pub mod notify;
+pub mod pid_file;
 pub mod processing;
 pub mod shutdown;
+pub mod status;
 pub mod storage;
 
-const DEFAULT_COLLECTION_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the daemon checks the record directory against the [RetentionPolicy].
+const RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How often the daemon samples RSS/CPU usage and refreshes `status.json`.
+const STATUS_REPORT_INTERVAL: Duration = Duration::from_secs(30);
 
-/// Represents the starting point for the daemon
-pub async fn start_daemon(dir: PathBuf) -> Result<()> {
+/// Default retention if `--retention-max-age-days` isn't passed: keep a year of history,
+/// uncapped by size.
+const DEFAULT_RETENTION_MAX_AGE_DAYS: u32 = 365;
+
+/// Builds the [RetentionPolicy] from [args::DaemonArgs]' `--retention-max-age-days`/
+/// `--retention-max-bytes`. A max age of `0` means "keep everything", matching the flag's help
+/// text, rather than the degenerate "delete everything older than today".
+fn resolve_retention_policy(retention_max_age_days: Option<u32>, retention_max_bytes: Option<u64>) -> RetentionPolicy {
+    let max_age_days = retention_max_age_days.unwrap_or(DEFAULT_RETENTION_MAX_AGE_DAYS);
+    RetentionPolicy {
+        max_age: (max_age_days > 0).then(|| ChronoDuration::days(max_age_days as i64)),
+        max_total_bytes: retention_max_bytes,
+    }
+}
+
+/// Represents the starting point for the daemon. Persists `collection_interval_secs`/
+/// `afk_threshold_secs` to [config::write_config] so a later reload (SIGHUP or the control
+/// socket's `Reload` command) has an actual file to re-read, instead of re-parsing the argv the
+/// process started with.
+pub async fn start_daemon(
+    dir: PathBuf,
+    notify_enabled: bool,
+    notify_budgets: Vec<BudgetRule>,
+    collection_interval_secs: u64,
+    afk_threshold_secs: u32,
+    object_store_url: Option<String>,
+    retention_max_age_days: Option<u32>,
+    retention_max_bytes: Option<u64>,
+) -> Result<()> {
     std::env::set_current_dir("/")?;
 
+    write_pid_file(&dir)?;
+    config::write_config(
+        &dir,
+        ReloadableConfig {
+            collection_interval_secs,
+            afk_threshold_secs,
+        },
+    )?;
+
     let (sender, receiver) = mpsc::channel::<RecordEvent>(10);
     let manager = GenericWindowManager::new()?;
 
     let shutdown_token = CancellationToken::new();
+    let (reload_sender, reload_receiver) = mpsc::unbounded_channel::<()>();
+    let (settings_sender, settings_receiver) = watch::channel(CollectorSettings {
+        collection_frequency: Duration::from_secs(collection_interval_secs),
+        afk_threshold_s: afk_threshold_secs,
+    });
+    let (paused_sender, paused_receiver) = watch::channel(false);
+    let (flush_sender, flush_receiver) = mpsc::channel::<oneshot::Sender<Result<()>>>(1);
+
+    let collector = create_collector(
+        sender,
+        manager,
+        &shutdown_token,
+        settings_receiver,
+        paused_receiver,
+        DefaultClock,
+        notify_enabled,
+        notify_budgets,
+    );
 
-    let collector = create_collector(sender, manager, &shutdown_token, DefaultClock);
-
-    let processor = create_processor(dir.join("records"), receiver, DefaultClock)?;
+    let status = StatusState::new();
+    let record_dir = dir.join("records");
+    let processor = create_processor(record_dir.clone(), receiver, DefaultClock, object_store_url.as_deref())?
+        .with_status(status.clone())
+        .with_flush_requests(flush_receiver);
+
+    let started_at = Utc::now();
+    let control_context = ControlContext {
+        status: status.clone(),
+        started_at,
+        record_dir: record_dir.clone(),
+        app_dir: dir.clone(),
+        paused: paused_sender,
+        settings: settings_sender.clone(),
+        flush: flush_sender,
+    };
 
-    let (_, collection_result, processing_result) = tokio::join!(
-        shutdown::detect_shutdown(shutdown_token),
+    let (_, _, _, _, _, collection_result, processing_result) = tokio::join!(
+        shutdown::detect_shutdown(shutdown_token.clone(), reload_sender),
+        watch_for_config_reload(reload_receiver, dir.clone(), settings_sender, shutdown_token.clone()),
+        enforce_retention_periodically(
+            record_dir,
+            resolve_retention_policy(retention_max_age_days, retention_max_bytes),
+            shutdown_token.clone(),
+        ),
+        run_status_reporter(
+            status,
+            std::process::id(),
+            started_at,
+            status_file_path(&dir),
+            STATUS_REPORT_INTERVAL,
+            shutdown_token.clone(),
+        ),
+        control::run_control_server(dir.clone(), control_context, shutdown_token),
         collector.run(),
         processor.run(),
     );
@@ -48,6 +149,8 @@ pub async fn start_daemon(dir: PathBuf) -> Result<()> {
         error!("Processing module got an error {:?}", processing_result);
     }
 
+    remove_pid_file(&dir);
+
     Ok(())
 }
 
@@ -55,48 +158,127 @@ fn create_collector(
     sender: mpsc::Sender<RecordEvent>,
     manager: impl WindowManager + 'static,
     shutdown_token: &CancellationToken,
+    settings: watch::Receiver<CollectorSettings>,
+    paused: watch::Receiver<bool>,
     clock: impl Clock,
+    notify_enabled: bool,
+    notify_budgets: Vec<BudgetRule>,
 ) -> DataCollectionModule {
     DataCollectionModule::new(
         sender,
         Box::new(manager),
         shutdown_token.clone(),
-        AfkEvaluator::from_seconds(60 * 2),
-        DEFAULT_COLLECTION_INTERVAL,
+        settings,
+        paused,
         Box::new(clock),
+        Notifier::new(notify_enabled, notify_budgets, Utc::now()),
     )
 }
 
+/// Re-reads `app_dir`'s config file every time a SIGHUP comes in over `reload`, and pushes the
+/// refreshed collection interval/AFK threshold into `settings` so the running collector picks them
+/// up on its next loop iteration, without tearing down and restarting the process. The file is
+/// what makes this an actual reload rather than a no-op: [start_daemon] writes it once at startup,
+/// and the control socket's `Reload` command (see [control::ControlRequest::Reload]) overwrites it
+/// whenever it carries new values, so a bare SIGHUP with no payload still picks up whatever was
+/// last applied.
+async fn watch_for_config_reload(
+    mut reload: mpsc::UnboundedReceiver<()>,
+    app_dir: PathBuf,
+    settings: watch::Sender<CollectorSettings>,
+    shutdown: CancellationToken,
+) {
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            received = reload.recv() => {
+                if received.is_none() {
+                    return;
+                }
+
+                match config::read_config(&app_dir) {
+                    Ok(cfg) => {
+                        info!(
+                            "Reloaded configuration: collection_interval={}s afk_threshold={}s",
+                            cfg.collection_interval_secs, cfg.afk_threshold_secs
+                        );
+                        let _ = settings.send(CollectorSettings {
+                            collection_frequency: Duration::from_secs(cfg.collection_interval_secs),
+                            afk_threshold_s: cfg.afk_threshold_secs,
+                        });
+                    }
+                    Err(e) => error!("Failed to reload configuration: {e:?}"),
+                }
+            }
+        }
+    }
+}
+
+/// How often [ProcessingModule] durably flushes the current record file even without new events,
+/// bounding how much buffered data a crash between day rollovers can lose.
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Picks the processing backend: the local filesystem by default, or an `object_store` URL (e.g.
+/// `s3://bucket/prefix`) when `--object-store-url` is set, so activity logs can be centralized or
+/// synced across machines instead of only ever living in `record_dir`.
 fn create_processor(
     record_dir: PathBuf,
     receiver: mpsc::Receiver<RecordEvent>,
-    clock: impl Clock,
-) -> Result<ProcessingModule<LocalSaver<RecordStorageImpl>>, anyhow::Error> {
+    clock: impl Clock + Clone,
+    object_store_url: Option<&str>,
+) -> Result<ProcessingModule<LocalSaver<RecordStorageBackend>>, anyhow::Error> {
+    let storage = match object_store_url {
+        Some(url) => RecordStorageBackend::ObjectStore(storage::object_store_storage::from_url(url)?),
+        None => RecordStorageBackend::Local(RecordStorageImpl::new(record_dir)?),
+    };
+    let saver = LocalSaver::new(storage, Box::new(clock.clone()));
+    Ok(ProcessingModule::new(receiver, saver)
+        .with_periodic_flush(Box::new(clock), DEFAULT_FLUSH_INTERVAL))
+}
+
+/// Periodically enforces `policy` against `record_dir` so a long-running install stays bounded,
+/// until `shutdown` is cancelled.
+async fn enforce_retention_periodically(
+    record_dir: PathBuf,
+    policy: RetentionPolicy,
+    shutdown: CancellationToken,
+) -> Result<()> {
     let storage = RecordStorageImpl::new(record_dir)?;
-    let saver = LocalSaver::new(storage, Box::new(clock));
-    Ok(ProcessingModule::new(receiver, saver))
+
+    while !shutdown.is_cancelled() {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(RETENTION_CHECK_INTERVAL) => {
+                if let Err(e) = storage.enforce_retention(policy).await {
+                    error!("Failed to enforce retention policy {e:?}");
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod daemon_tests {
-    use std::{fs, time::Duration};
+    use std::fs;
 
     use anyhow::Result;
-    use async_trait::async_trait;
-    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
     use tempfile::tempdir;
-    use tokio::{sync::mpsc, time::Instant};
+    use tokio::sync::mpsc;
     use tokio_util::sync::CancellationToken;
 
     use crate::{
         daemon::{
+            collection::collector::CollectorSettings,
             create_collector, create_processor,
             storage::{
                 record_event::RecordEvent,
                 record_storage::{RecordStorage, RecordStorageImpl},
             },
         },
-        utils::{clock::Clock, logging::TEST_LOGGING},
+        utils::{clock::AnchoredClock, logging::TEST_LOGGING},
         window_api::{ActiveWindowData, MockWindowManager},
     };
 
@@ -120,34 +302,19 @@ mod daemon_tests {
         ]
     }
 
-    #[derive(Clone)]
-    struct TestClock {
-        start_time: DateTime<Utc>,
-        reference: Instant,
-    }
-
-    #[async_trait]
-    impl Clock for TestClock {
-        fn time(&self) -> DateTime<Utc> {
-            self.start_time + self.reference.elapsed()
-        }
-
-        fn instant(&self) -> Instant {
-            Instant::now()
-        }
-
-        async fn sleep(&self, duration: Duration) {
-            tokio::time::sleep(duration).await;
-        }
-
-        async fn sleep_until(&self, instant: tokio::time::Instant) {
-            tokio::time::sleep_until(instant).await;
-        }
-    }
-
-    /// Very simple smoke test to check if the application is working properly. It can be improved
-    /// by warping time so that it takes 10 times less time, but for now we have what we have.
-    #[tokio::test]
+    /// How many virtual days the collector/processor pipeline is driven through. Picking more than
+    /// one day exercises [crate::daemon::processing::local_save::LocalSaver]'s date-rollover logic
+    /// across real midnight boundaries, not just a single file.
+    const DAYS_TO_SIMULATE: i64 = 3;
+
+    /// Smoke test that checks the collection/processing pipeline is wired up correctly and that
+    /// [LocalSaver](crate::daemon::processing::local_save::LocalSaver) rotates its record file at
+    /// every day boundary. Uses [AnchoredClock] together with tokio's paused-time machinery to
+    /// drive the pipeline through several simulated days in milliseconds of real wall-clock time,
+    /// instead of real sleeps: every wait in the collector/processor loops goes through the
+    /// [Clock](crate::utils::clock::Clock) trait, so `tokio::time::advance` can fast-forward
+    /// through them deterministically.
+    #[tokio::test(start_paused = true)]
     async fn smoke_test_daemon() -> Result<()> {
         *TEST_LOGGING;
         let mut mock_window_manager = MockWindowManager::new();
@@ -157,30 +324,35 @@ mod daemon_tests {
         let mut items = test_items().into_iter().cycle();
         mock_window_manager
             .expect_get_active_window_data()
-            .returning(move || Ok(items.next().unwrap()))
-            .times(..7);
+            .returning(move || Ok(items.next().unwrap()));
 
         let shutdown_token = CancellationToken::new();
 
         let (sender, receiver) = mpsc::channel::<RecordEvent>(10);
-        let test_clock = TestClock {
-            start_time: Utc.from_utc_datetime(&TEST_START_DATE),
-            reference: Instant::now(),
-        };
+        let clock = AnchoredClock::new(Utc.from_utc_datetime(&TEST_START_DATE));
+        let (_settings_sender, settings_receiver) = tokio::sync::watch::channel(CollectorSettings {
+            collection_frequency: std::time::Duration::from_secs(1),
+            afk_threshold_s: 120,
+        });
+        let (_paused_sender, paused_receiver) = tokio::sync::watch::channel(false);
         let collector = create_collector(
             sender,
             mock_window_manager,
             &shutdown_token,
-            test_clock.clone(),
+            settings_receiver,
+            paused_receiver,
+            clock.clone(),
+            false,
+            Vec::new(),
         );
 
         let dir = tempdir()?;
 
-        let processor = create_processor(dir.path().to_path_buf(), receiver, test_clock.clone())?;
+        let processor = create_processor(dir.path().to_path_buf(), receiver, clock.clone(), None)?;
 
         let (_, collection_result, processing_result) = tokio::join!(
             async {
-                tokio::time::sleep(Duration::from_millis(5500)).await;
+                tokio::time::advance(chrono::Duration::days(DAYS_TO_SIMULATE).to_std().unwrap()).await;
                 shutdown_token.cancel()
             },
             collector.run(),
@@ -190,15 +362,41 @@ mod daemon_tests {
         collection_result?;
         processing_result?;
 
+        let storage = RecordStorageImpl::new(dir.path().to_path_buf())?;
+        let start_date = TEST_START_DATE.date();
+        for offset in 0..=DAYS_TO_SIMULATE {
+            let day = start_date + chrono::Duration::days(offset);
+            let data = storage.get_data_for(day).await?;
+            assert!(!data.is_empty(), "expected at least one record for {day}, got none");
+        }
+
         let files = fs::read_dir(dir.path())?.collect::<Vec<_>>();
-        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files.len(),
+            (DAYS_TO_SIMULATE + 1) as usize,
+            "expected one record file per day crossed"
+        );
 
-        let storage = RecordStorageImpl::new(dir.path().to_path_buf())?;
+        Ok(())
+    }
 
-        let data = storage.get_data_for(TEST_START_DATE.date()).await?;
+    #[test]
+    fn resolve_retention_policy_defaults_to_a_year_uncapped_by_size() {
+        let policy = super::resolve_retention_policy(None, None);
+        assert_eq!(policy.max_age, Some(chrono::Duration::days(365)));
+        assert_eq!(policy.max_total_bytes, None);
+    }
 
-        assert_eq!(data.len(), 4);
+    #[test]
+    fn resolve_retention_policy_honors_explicit_age_and_byte_cap() {
+        let policy = super::resolve_retention_policy(Some(30), Some(1_000_000));
+        assert_eq!(policy.max_age, Some(chrono::Duration::days(30)));
+        assert_eq!(policy.max_total_bytes, Some(1_000_000));
+    }
 
-        Ok(())
+    #[test]
+    fn resolve_retention_policy_zero_days_means_keep_everything() {
+        let policy = super::resolve_retention_policy(Some(0), None);
+        assert_eq!(policy.max_age, None);
     }
 }
@@ -0,0 +1,196 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use tracing::warn;
+
+use super::storage::record_event::RecordEvent;
+use crate::cli::timeline::clean_process_name;
+
+/// How long to wait before re-alerting about the same exceeded budget, so a user who's already
+/// seen the notification isn't spammed on every sampling tick they stay over it.
+const BUDGET_ALERT_DEBOUNCE: Duration = Duration::minutes(15);
+
+/// A single `--notify-budget <process>=<duration>` rule, e.g. `firefox=2h`.
+#[derive(Debug, Clone)]
+pub struct BudgetRule {
+    pub process_name: String,
+    pub limit: Duration,
+}
+
+impl Display for BudgetRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}h", self.process_name, self.limit.num_hours())
+    }
+}
+
+impl FromStr for BudgetRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (process_name, duration) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected `<process>=<duration>`, e.g. `firefox=2h`, got {s:?}"))?;
+        let limit = parse_duration(duration)
+            .with_context(|| format!("Failed to parse budget duration {duration:?}"))?;
+        Ok(Self { process_name: process_name.to_string(), limit })
+    }
+}
+
+/// Parses a plain `<number><unit>` duration where unit is `h`, `m`, or `s` (e.g. `2h`, `90m`).
+/// Intentionally narrow rather than a general-purpose duration parser, since budgets are only ever
+/// given as whole hours/minutes/seconds on the command line.
+fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split_at = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| anyhow!("Missing unit (h/m/s) on duration {s:?}"))?;
+    let (value, unit) = s.split_at(split_at);
+    let value: i64 = value.parse().with_context(|| format!("{value:?} isn't a valid number"))?;
+    match unit {
+        "h" => Ok(Duration::hours(value)),
+        "m" => Ok(Duration::minutes(value)),
+        "s" => Ok(Duration::seconds(value)),
+        other => Err(anyhow!("Unknown duration unit {other:?}, expected one of h/m/s")),
+    }
+}
+
+/// Watches recorded events for AFK transitions and per-process budget overruns, firing desktop
+/// notifications for either. Disabled by default since not everyone wants whatawhat to do more
+/// than passively record.
+pub struct Notifier {
+    enabled: bool,
+    budgets: Vec<BudgetRule>,
+    day: NaiveDate,
+    usage: HashMap<String, Duration>,
+    previous_afk: Option<bool>,
+    last_budget_alert: HashMap<String, DateTime<Utc>>,
+}
+
+impl Notifier {
+    pub fn new(enabled: bool, budgets: Vec<BudgetRule>, now: DateTime<Utc>) -> Self {
+        Self {
+            enabled,
+            budgets,
+            day: now.date_naive(),
+            usage: HashMap::new(),
+            previous_afk: None,
+            last_budget_alert: HashMap::new(),
+        }
+    }
+
+    /// Folds one collected event into the running state, firing a notification if it crosses the
+    /// AFK threshold in either direction or pushes a watched process over its daily budget.
+    /// `tick_duration` is how long this event is assumed to cover, i.e. the collection interval.
+    pub fn observe(&mut self, record: &RecordEvent, tick_duration: Duration) {
+        if !self.enabled {
+            return;
+        }
+
+        let today = record.timestamp.date_naive();
+        if today != self.day {
+            self.day = today;
+            self.usage.clear();
+            self.last_budget_alert.clear();
+        }
+
+        self.observe_afk_transition(record.afk);
+
+        if !record.afk {
+            self.observe_budget(&record.process_name, tick_duration, record.timestamp);
+        }
+    }
+
+    fn observe_afk_transition(&mut self, afk: bool) {
+        // The first observation only establishes the starting state; it isn't a crossing.
+        let previous = self.previous_afk.replace(afk);
+        if previous.is_none() || previous == Some(afk) {
+            return;
+        }
+
+        if afk {
+            self.notify("Went AFK", "whatawhat noticed you stepped away");
+        } else {
+            self.notify("Welcome back", "whatawhat resumed tracking active usage");
+        }
+    }
+
+    fn observe_budget(&mut self, process_name: &str, tick_duration: Duration, now: DateTime<Utc>) {
+        // `process_name` is the full executable path (see `ActiveWindowData::process_name`), but
+        // `--notify-budget` rules are written against the basename, e.g. `firefox=2h`, so compare
+        // basenames the same way `clean_process_name` does for display.
+        let basename = clean_process_name(process_name);
+        let Some(rule) = self
+            .budgets
+            .iter()
+            .find(|rule| rule.process_name.eq_ignore_ascii_case(&basename))
+        else {
+            return;
+        };
+
+        let accumulated = self
+            .usage
+            .entry(process_name.to_string())
+            .or_insert_with(Duration::zero);
+        *accumulated += tick_duration;
+
+        if *accumulated < rule.limit {
+            return;
+        }
+
+        let already_alerted = self
+            .last_budget_alert
+            .get(process_name)
+            .is_some_and(|last| now - *last < BUDGET_ALERT_DEBOUNCE);
+        if already_alerted {
+            return;
+        }
+        self.last_budget_alert.insert(process_name.to_string(), now);
+
+        self.notify(
+            "Usage budget exceeded",
+            &format!("{process_name} has been used for over {}h today", rule.limit.num_hours()),
+        );
+    }
+
+    fn notify(&self, summary: &str, body: &str) {
+        if let Err(e) = notify_rust::Notification::new().summary(summary).body(body).show() {
+            warn!("Failed to show desktop notification: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_rule_matches_full_executable_path_against_its_basename() {
+        let mut notifier = Notifier::new(
+            true,
+            vec![BudgetRule { process_name: "firefox".to_string(), limit: Duration::hours(1) }],
+            Utc::now(),
+        );
+
+        notifier.observe_budget("/usr/lib/firefox/firefox", Duration::minutes(5), Utc::now());
+
+        assert_eq!(
+            notifier.usage.get("/usr/lib/firefox/firefox"),
+            Some(&Duration::minutes(5)),
+            "rule for `firefox` should have matched the full path and accumulated usage"
+        );
+    }
+
+    #[test]
+    fn budget_rule_ignores_unrelated_processes() {
+        let mut notifier = Notifier::new(
+            true,
+            vec![BudgetRule { process_name: "firefox".to_string(), limit: Duration::hours(1) }],
+            Utc::now(),
+        );
+
+        notifier.observe_budget("/usr/bin/vim", Duration::minutes(5), Utc::now());
+
+        assert!(notifier.usage.is_empty());
+    }
+}
@@ -0,0 +1,36 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+/// Where the daemon writes its PID at startup, so the CLI can find and signal the right process
+/// instead of inferring it from the executable name (fragile if multiple instances share a name,
+/// or the process was replaced by an unrelated one reusing the same path).
+pub fn pid_file_path(app_dir: &Path) -> PathBuf {
+    app_dir.join("whatawhat.pid")
+}
+
+/// Writes the current process' id to the PID file, overwriting any stale one left behind by an
+/// unclean previous shutdown.
+pub fn write_pid_file(app_dir: &Path) -> Result<()> {
+    fs::write(pid_file_path(app_dir), std::process::id().to_string())
+        .context("Failed to write PID file")
+}
+
+/// Reads back the PID written by [write_pid_file].
+pub fn read_pid_file(app_dir: &Path) -> Result<u32> {
+    let contents = fs::read_to_string(pid_file_path(app_dir))
+        .context("Failed to read PID file; is the daemon running?")?;
+    contents
+        .trim()
+        .parse()
+        .context("PID file contents aren't a valid process id")
+}
+
+/// Removes the PID file. Used both by the daemon on a clean exit and by the CLI after it has
+/// successfully stopped the process the file pointed at.
+pub fn remove_pid_file(app_dir: &Path) {
+    let _ = fs::remove_file(pid_file_path(app_dir));
+}
@@ -29,24 +29,31 @@ impl<R: RecordStorage> LocalSaver<R> {
         }
     }
 
-    async fn move_file_handle(&mut self) -> Result<R::RecordFile> {
-        let current_file = self.current_handle.take();
+    /// Returns a handle for `now`'s record file, rotating away from a stale one from an earlier
+    /// day. Always leaves the returned handle stored in `self.current_handle` so `finalize` (and
+    /// the next call to this function) sees it rather than silently losing it after the first
+    /// rotation.
+    async fn move_file_handle(&mut self) -> Result<&mut R::RecordFile> {
         let now = self.date_provider.time().date_naive();
 
-        match current_file {
-            Some(mut file) if file.get_date() != now => {
+        let stale = matches!(&self.current_handle, Some(file) if file.get_date() != now);
+        if stale {
+            if let Some(mut file) = self.current_handle.take() {
                 file.flush().await?;
             }
-            Some(v) => return Ok(v),
-            None => {}
-        };
-        self.records_storage.create_or_append_record(now).await
+        }
+
+        if self.current_handle.is_none() {
+            self.current_handle = Some(self.records_storage.create_or_append_record(now).await?);
+        }
+
+        Ok(self.current_handle.as_mut().expect("just set above"))
     }
 }
 
 impl<R: RecordStorage> EventProcessor for LocalSaver<R> {
     async fn process_next(&mut self, message: RecordEvent) -> anyhow::Result<()> {
-        let mut active_file = self.move_file_handle().await?;
+        let active_file = self.move_file_handle().await?;
 
         active_file
             .append(vec![UsageRecordEntity {
@@ -67,3 +74,105 @@ impl<R: RecordStorage> EventProcessor for LocalSaver<R> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use chrono::{NaiveDate, Utc};
+    use futures::Stream;
+
+    use crate::{daemon::storage::entities::UsageIntervalEntity, utils::clock::AnchoredClock};
+
+    use super::*;
+
+    /// A [RecordFileHandle] that counts its `flush` calls instead of touching a real file, so a
+    /// test can prove `finalize`/rotation actually reach the handle `LocalSaver` is holding,
+    /// rather than relying on `append`'s own durability to mask a handle that never gets flushed.
+    struct CountingRecordFile {
+        date: NaiveDate,
+        flush_calls: Arc<AtomicUsize>,
+    }
+
+    impl RecordFileHandle for CountingRecordFile {
+        async fn append(&mut self, _usage_records: Vec<UsageRecordEntity>) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_date(&self) -> NaiveDate {
+            self.date
+        }
+
+        async fn flush(&mut self) -> Result<()> {
+            self.flush_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    struct CountingStorage {
+        flush_calls: Arc<AtomicUsize>,
+    }
+
+    impl RecordStorage for CountingStorage {
+        type RecordFile = CountingRecordFile;
+
+        async fn create_or_append_record(&self, date: NaiveDate) -> Result<Self::RecordFile> {
+            Ok(CountingRecordFile {
+                date,
+                flush_calls: self.flush_calls.clone(),
+            })
+        }
+
+        async fn get_data_for(&self, _date: NaiveDate) -> Result<Vec<UsageIntervalEntity>> {
+            Ok(vec![])
+        }
+
+        async fn stream_data_for(
+            &self,
+            _date: NaiveDate,
+        ) -> Result<impl Stream<Item = Result<UsageIntervalEntity>> + Send + 'static> {
+            Ok(futures::stream::empty())
+        }
+    }
+
+    fn event(now: chrono::DateTime<Utc>) -> RecordEvent {
+        RecordEvent {
+            window_name: "window".into(),
+            process_name: "process".into(),
+            afk: false,
+            timestamp: now,
+        }
+    }
+
+    /// Regression test for a bug where `move_file_handle` returned the rotated/created handle
+    /// without ever storing it back into `self.current_handle`, leaving it permanently `None`
+    /// after the very first event and making `finalize`'s flush a silent no-op.
+    #[tokio::test]
+    async fn finalize_flushes_the_handle_used_by_process_next() -> Result<()> {
+        let flush_calls = Arc::new(AtomicUsize::new(0));
+        let clock = AnchoredClock::new(Utc::now());
+        let now = clock.time();
+
+        let mut saver = LocalSaver::new(
+            CountingStorage {
+                flush_calls: flush_calls.clone(),
+            },
+            Box::new(clock),
+        );
+
+        saver.process_next(event(now)).await?;
+        assert_eq!(flush_calls.load(Ordering::SeqCst), 0, "flush shouldn't fire before finalize");
+
+        saver.finalize().await?;
+        assert_eq!(
+            flush_calls.load(Ordering::SeqCst),
+            1,
+            "finalize should flush the handle process_next just wrote through"
+        );
+
+        Ok(())
+    }
+}
@@ -1,9 +1,19 @@
+use std::{sync::Arc, time::Duration};
+
 use anyhow::Result;
 use module::EventProcessor;
-use tokio::sync::mpsc::Receiver;
+use tokio::{
+    sync::{
+        mpsc::{Receiver, Sender},
+        oneshot,
+    },
+    time::Instant,
+};
 use tracing::{debug, error, info};
 
-use super::storage::record_event::RecordEvent;
+use crate::utils::clock::Clock;
+
+use super::{status::StatusState, storage::record_event::RecordEvent};
 
 pub mod local_save;
 pub mod module;
@@ -13,6 +23,9 @@ pub mod module;
 pub struct ProcessingModule<Processor> {
     receiver: Receiver<RecordEvent>,
     processor: Processor,
+    status: Option<Arc<StatusState>>,
+    flush_requests: Option<Receiver<oneshot::Sender<Result<()>>>>,
+    periodic_flush: Option<(Box<dyn Clock>, Duration)>,
 }
 
 impl<P: EventProcessor> ProcessingModule<P> {
@@ -20,18 +33,72 @@ impl<P: EventProcessor> ProcessingModule<P> {
         Self {
             receiver,
             processor,
+            status: None,
+            flush_requests: None,
+            periodic_flush: None,
         }
     }
 
+    /// Bumps `status`'s processed/errored counters for every event this module handles, so a
+    /// periodic reporter can fold them into a [StatusSnapshot](super::status::StatusSnapshot).
+    pub fn with_status(mut self, status: Arc<StatusState>) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Lets the control socket's `flush` command force [EventProcessor::finalize] without waiting
+    /// for the daemon to shut down, by replying on the `oneshot::Sender` it sends over `requests`.
+    pub fn with_flush_requests(mut self, requests: Receiver<oneshot::Sender<Result<()>>>) -> Self {
+        self.flush_requests = Some(requests);
+        self
+    }
+
+    /// Calls [EventProcessor::finalize] every `interval` even with no events queued, so an idle
+    /// period between a crash and the next day rollover doesn't silently discard buffered data.
+    /// Uses `clock` rather than bare `tokio::time` so this is driven in lockstep with the rest of
+    /// the pipeline under a virtual clock in tests.
+    pub fn with_periodic_flush(mut self, clock: Box<dyn Clock>, interval: Duration) -> Self {
+        self.periodic_flush = Some((clock, interval));
+        self
+    }
+
     pub async fn run(mut self) -> Result<()> {
-        while let Some(record) = self.receiver.recv().await {
-            debug!("Processing event {:?}", record);
-            match self.processor.process_next(record.clone()).await {
-                Ok(_) => {
-                    info!("Processed event {:?}", record)
+        let mut flush_deadline = self.next_flush_deadline();
+
+        loop {
+            tokio::select! {
+                record = self.receiver.recv() => {
+                    let Some(record) = record else { break };
+
+                    debug!("Processing event {:?}", record);
+                    if let Some(status) = &self.status {
+                        status.record_window(record.window_name.clone());
+                    }
+                    match self.processor.process_next(record.clone()).await {
+                        Ok(_) => {
+                            info!("Processed event {:?}", record);
+                            if let Some(status) = &self.status {
+                                status.record_success();
+                            }
+                        }
+                        Err(e) => {
+                            error!("Error processing event {:?}: {e:?}", record);
+                            if let Some(status) = &self.status {
+                                status.record_error();
+                            }
+                        }
+                    }
+                }
+                Some(reply) = Self::next_flush_request(&mut self.flush_requests) => {
+                    info!("Flushing on demand");
+                    let _ = reply.send(self.processor.finalize().await);
                 }
-                Err(e) => {
-                    error!("Error processing event {:?}: {e:?}", record)
+                _ = Self::sleep_until(&self.periodic_flush, flush_deadline) => {
+                    debug!("Flushing on periodic timer");
+                    if let Err(e) = self.processor.finalize().await {
+                        error!("Periodic flush failed: {e:?}");
+                    }
+                    flush_deadline = self.next_flush_deadline();
                 }
             }
         }
@@ -40,4 +107,109 @@ impl<P: EventProcessor> ProcessingModule<P> {
         self.receiver.close();
         result
     }
+
+    fn next_flush_deadline(&self) -> Option<Instant> {
+        self.periodic_flush
+            .as_ref()
+            .map(|(clock, interval)| clock.instant() + *interval)
+    }
+
+    async fn next_flush_request(
+        requests: &mut Option<Receiver<oneshot::Sender<Result<()>>>>,
+    ) -> Option<oneshot::Sender<Result<()>>> {
+        match requests {
+            Some(requests) => requests.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    async fn sleep_until(periodic_flush: &Option<(Box<dyn Clock>, Duration)>, deadline: Option<Instant>) {
+        match (periodic_flush, deadline) {
+            (Some((clock, _)), Some(deadline)) => clock.sleep_until(deadline).await,
+            _ => std::future::pending().await,
+        }
+    }
+}
+
+/// Requests a [EventProcessor::finalize] from a running [ProcessingModule] over the channel set up
+/// by [ProcessingModule::with_flush_requests], and waits for it to complete.
+pub async fn request_flush(requests: &Sender<oneshot::Sender<Result<()>>>) -> Result<()> {
+    let (reply_sender, reply_receiver) = oneshot::channel();
+    requests
+        .send(reply_sender)
+        .await
+        .map_err(|_| anyhow::anyhow!("Processing module is not running"))?;
+    reply_receiver
+        .await
+        .map_err(|_| anyhow::anyhow!("Processing module dropped the flush request"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use chrono::Utc;
+    use tokio::sync::mpsc;
+
+    use crate::{daemon::storage::record_event::RecordEvent, utils::clock::AnchoredClock};
+
+    use super::{module::EventProcessor, ProcessingModule};
+
+    /// A no-op [EventProcessor] that only counts [EventProcessor::finalize] calls. Measuring the
+    /// periodic timer through real storage wouldn't prove anything: `LocalSaver`'s backing
+    /// `append()` already durably writes every record on its own, and `ProcessingModule::run`'s
+    /// unconditional `finalize()` after the channel closes would make on-disk data show up even if
+    /// the periodic timer were never wired in at all. Counting calls while the channel is still
+    /// open isolates the timer specifically.
+    #[derive(Default)]
+    struct CountingProcessor {
+        finalize_calls: Arc<AtomicUsize>,
+    }
+
+    impl EventProcessor for CountingProcessor {
+        async fn process_next(&mut self, _message: RecordEvent) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn finalize(&mut self) -> anyhow::Result<()> {
+            self.finalize_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    /// Advancing virtual time past the flush interval, with the channel still open and no new
+    /// events queued, should still call [EventProcessor::finalize] repeatedly on its own. Asserting
+    /// this before dropping `sender` rules out the run loop's unconditional post-close finalize as
+    /// the explanation.
+    #[tokio::test(start_paused = true)]
+    async fn periodic_flush_fires_while_idle() -> anyhow::Result<()> {
+        let finalize_calls = Arc::new(AtomicUsize::new(0));
+        let processor = CountingProcessor {
+            finalize_calls: finalize_calls.clone(),
+        };
+        let clock = AnchoredClock::new(Utc::now());
+        let flush_interval = std::time::Duration::from_secs(30);
+
+        let (sender, receiver) = mpsc::channel::<RecordEvent>(1);
+        let module = ProcessingModule::new(receiver, processor)
+            .with_periodic_flush(Box::new(clock), flush_interval);
+
+        let handle = tokio::spawn(module.run());
+
+        tokio::time::advance(flush_interval * 3 + std::time::Duration::from_secs(1)).await;
+
+        let calls = finalize_calls.load(Ordering::SeqCst);
+        assert!(
+            calls >= 3,
+            "expected periodic flush to fire roughly every {flush_interval:?} while idle, got {calls} calls"
+        );
+
+        drop(sender);
+        handle.await??;
+
+        Ok(())
+    }
 }
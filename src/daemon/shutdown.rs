@@ -1,14 +1,61 @@
-use tokio::select;
+use tokio::{select, sync::mpsc};
 use tokio_util::sync::CancellationToken;
+use tracing::info;
 
-/// Detects signals sent to the process. This works with limmited success. 
+/// Detects signals sent to the process and cancels `shutdown` so the collection/processing loops
+/// can notice, stop, and flush pending records before the process exits. Sends on `reload` instead
+/// of cancelling whenever a signal asks for a live config reload rather than a shutdown.
 ///
-/// On Windows detached processes can't detect signals sent to them, so this should be enhanced in the future to 
-/// support another way of sending signals.
-pub async fn detect_shutdown(cancelation: CancellationToken) {
-    select! {
-        _ = tokio::signal::ctrl_c() => {
-            cancelation.cancel();
-        },
-    };
+/// On Unix this watches SIGINT (ctrl-c), SIGTERM (how the CLI `Stop` command asks a PID-file
+/// identified daemon to shut down, including a hard `kill_previous_daemons`), and SIGHUP (the
+/// conventional "reload your configuration" signal). On Windows, a detached process has no SIGHUP
+/// equivalent, so it only watches `ctrl_c` plus the `ctrl_close`/`ctrl_shutdown` console control
+/// events that fire when the console window is closed or the system is shutting down.
+pub async fn detect_shutdown(shutdown: CancellationToken, reload: mpsc::UnboundedSender<()>) {
+    #[cfg(unix)]
+    {
+        let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler");
+        let mut sighup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .expect("Failed to install SIGHUP handler");
+
+        loop {
+            select! {
+                _ = tokio::signal::ctrl_c() => {
+                    info!("Received SIGINT, shutting down");
+                    break;
+                },
+                _ = sigterm.recv() => {
+                    info!("Received SIGTERM, shutting down");
+                    break;
+                },
+                _ = sighup.recv() => {
+                    info!("Received SIGHUP, reloading configuration");
+                    // Best-effort: if nothing is listening there's nothing to reload.
+                    let _ = reload.send(());
+                },
+            }
+        }
+    }
+    #[cfg(windows)]
+    {
+        let mut ctrl_close =
+            tokio::signal::windows::ctrl_close().expect("Failed to install ctrl-close handler");
+        let mut ctrl_shutdown = tokio::signal::windows::ctrl_shutdown()
+            .expect("Failed to install ctrl-shutdown handler");
+
+        select! {
+            _ = tokio::signal::ctrl_c() => {
+                info!("Received ctrl-c, shutting down");
+            },
+            _ = ctrl_close.recv() => {
+                info!("Received ctrl-close, shutting down");
+            },
+            _ = ctrl_shutdown.recv() => {
+                info!("Received ctrl-shutdown, shutting down");
+            },
+        };
+    }
+
+    shutdown.cancel();
 }
@@ -0,0 +1,134 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sysinfo::{get_current_pid, System};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// A point-in-time health snapshot of the running daemon, persisted to disk so `whatawhat status`
+/// can tell whether the collector is alive and healthy without grepping the rotated log files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusSnapshot {
+    pub instance_id: u32,
+    pub version: String,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub rss_bytes: u64,
+    pub cpu_usage_percent: f32,
+    pub events_processed: u64,
+    pub events_errored: u64,
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+/// Where the latest [StatusSnapshot] is persisted inside the application directory.
+pub fn status_file_path(app_dir: &Path) -> PathBuf {
+    app_dir.join("status.json")
+}
+
+/// Shared, lock-free counters that [ProcessingModule](super::processing::ProcessingModule) bumps
+/// as it processes events. The periodic reporter below reads them to fill in a snapshot alongside
+/// the RSS/CPU usage it samples itself.
+#[derive(Default)]
+pub struct StatusState {
+    events_processed: AtomicU64,
+    events_errored: AtomicU64,
+    last_event_at: AtomicI64,
+    // A `Mutex` instead of an atomic since this holds a `String`; it's only touched once per
+    // processed event, so the lock is never contended enough to matter.
+    last_window: Mutex<Option<String>>,
+}
+
+impl StatusState {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn record_success(&self) {
+        self.events_processed.fetch_add(1, Ordering::Relaxed);
+        self.last_event_at
+            .store(Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self) {
+        self.events_errored.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Remembers the window name from the most recently processed event, so the control socket's
+    /// `status` command can report the currently active window without a separate channel.
+    pub fn record_window(&self, window_name: String) {
+        *self.last_window.lock().unwrap() = Some(window_name);
+    }
+
+    pub fn active_window(&self) -> Option<String> {
+        self.last_window.lock().unwrap().clone()
+    }
+
+    fn last_event_at(&self) -> Option<DateTime<Utc>> {
+        match self.last_event_at.load(Ordering::Relaxed) {
+            0 => None,
+            secs => DateTime::from_timestamp(secs, 0),
+        }
+    }
+}
+
+/// Samples this process' RSS/CPU usage and writes a fresh [StatusSnapshot] to `path` on every
+/// `interval` tick, until `shutdown` is cancelled.
+pub async fn run_status_reporter(
+    status: Arc<StatusState>,
+    instance_id: u32,
+    started_at: DateTime<Utc>,
+    path: PathBuf,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let pid = get_current_pid().map_err(|e| anyhow!("Failed to resolve current pid: {e}"))?;
+    let mut system = System::new_all();
+
+    loop {
+        system.refresh_all();
+        let (rss_bytes, cpu_usage_percent) = system
+            .process(pid)
+            .map(|process| (process.memory(), process.cpu_usage()))
+            .unwrap_or_default();
+
+        let snapshot = StatusSnapshot {
+            instance_id,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at,
+            updated_at: Utc::now(),
+            rss_bytes,
+            cpu_usage_percent,
+            events_processed: status.events_processed.load(Ordering::Relaxed),
+            events_errored: status.events_errored.load(Ordering::Relaxed),
+            last_event_at: status.last_event_at(),
+        };
+
+        if let Err(e) = write_snapshot(&path, &snapshot).await {
+            warn!("Failed to write status snapshot {e:?}");
+        }
+
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+
+    Ok(())
+}
+
+async fn write_snapshot(path: &Path, snapshot: &StatusSnapshot) -> Result<()> {
+    let json = serde_json::to_vec_pretty(snapshot)?;
+    tokio::fs::write(path, json)
+        .await
+        .context("Failed to write status file")?;
+    Ok(())
+}
@@ -7,6 +7,7 @@
 pub mod record_event;
 pub mod record_storage;
 pub mod entities;
+pub mod object_store_storage;
 
 
 
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use chrono::NaiveDate;
+use futures::{Stream, StreamExt, stream};
+use object_store::{Error as ObjectStoreError, ObjectStore, PutMode, PutOptions, UpdateVersion, path::Path as ObjectPath};
+use tracing::warn;
+use url::Url;
+
+use crate::utils::time::date_to_record_name;
+
+use super::{
+    entities::{UsageIntervalEntity, UsageRecordEntity},
+    record_storage::{RecordFileHandle, RecordStorage, RecordStorageImpl, collapse_records},
+};
+
+/// [RecordStorage] backed by the `object_store` crate, so activity logs can be centralized on
+/// S3/GCS/Azure or simply kept on a synced local folder instead of the plain filesystem. Objects
+/// are laid out using the same per-UTC-day naming as [super::record_storage::RecordStorageImpl],
+/// so the two backends are interchangeable.
+pub struct ObjectStoreRecordStorage {
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+}
+
+impl ObjectStoreRecordStorage {
+    pub fn new(store: Arc<dyn ObjectStore>, prefix: ObjectPath) -> Self {
+        Self { store, prefix }
+    }
+
+    fn object_path(&self, date: NaiveDate) -> ObjectPath {
+        self.prefix.child(date_to_record_name(date))
+    }
+}
+
+impl RecordStorage for ObjectStoreRecordStorage {
+    type RecordFile = ObjectStoreRecordFile;
+
+    async fn create_or_append_record(&self, date: NaiveDate) -> Result<Self::RecordFile> {
+        let path = self.object_path(date);
+        let (committed, version) = get_or_empty(&self.store, &path).await?;
+
+        Ok(ObjectStoreRecordFile {
+            store: self.store.clone(),
+            path,
+            date,
+            committed,
+            pending: Vec::new(),
+            version,
+        })
+    }
+
+    async fn get_data_for(&self, date: NaiveDate) -> Result<Vec<UsageIntervalEntity>> {
+        let path = self.object_path(date);
+        let (intervals, _) = get_or_empty(&self.store, &path).await?;
+        Ok(intervals)
+    }
+
+    async fn stream_data_for(
+        &self,
+        date: NaiveDate,
+    ) -> Result<impl Stream<Item = Result<UsageIntervalEntity>> + Send + 'static> {
+        let intervals = self.get_data_for(date).await?;
+        Ok(stream::iter(intervals).map(Ok))
+    }
+}
+
+/// A read-modify-write handle over a day's object. Object stores have no append, so appended
+/// records are buffered in `pending` (seeded by a GET of the existing object into `committed`)
+/// and only actually written out on [RecordFileHandle::flush], using the same [collapse_records]
+/// merge the filesystem backend uses.
+pub struct ObjectStoreRecordFile {
+    store: Arc<dyn ObjectStore>,
+    path: ObjectPath,
+    date: NaiveDate,
+    committed: Vec<UsageIntervalEntity>,
+    pending: Vec<UsageRecordEntity>,
+    version: Option<UpdateVersion>,
+}
+
+impl RecordFileHandle for ObjectStoreRecordFile {
+    async fn append(&mut self, usage_records: Vec<UsageRecordEntity>) -> Result<()> {
+        self.pending.extend(usage_records);
+        Ok(())
+    }
+
+    fn get_date(&self) -> NaiveDate {
+        self.date
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        // Conditional PUT means another writer might have updated the object since we last read
+        // it, in which case we re-GET, re-merge on top of the fresher state and retry.
+        loop {
+            let mut candidate = self.committed.clone();
+            let last = candidate.pop();
+            candidate.extend(collapse_records(last, self.pending.clone()));
+
+            let mut buffer = Vec::<u8>::new();
+            for interval in &candidate {
+                serde_json::to_writer(&mut buffer, interval)?;
+                buffer.push(b'\n');
+            }
+
+            let mode = match &self.version {
+                Some(version) => PutMode::Update(version.clone()),
+                None => PutMode::Create,
+            };
+
+            match self
+                .store
+                .put_opts(
+                    &self.path,
+                    Bytes::from(buffer).into(),
+                    PutOptions {
+                        mode,
+                        ..Default::default()
+                    },
+                )
+                .await
+            {
+                Ok(result) => {
+                    self.committed = candidate;
+                    self.pending.clear();
+                    self.version = Some(UpdateVersion {
+                        e_tag: result.e_tag,
+                        version: result.version,
+                    });
+                    return Ok(());
+                }
+                Err(ObjectStoreError::Precondition { .. } | ObjectStoreError::AlreadyExists { .. }) => {
+                    warn!("Concurrent writer detected for {:?}, retrying merge", self.path);
+                    let (remote, version) = get_or_empty(&self.store, &self.path).await?;
+                    self.committed = remote;
+                    self.version = version;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// GETs and parses an object's day, treating a missing object the same as an empty day.
+async fn get_or_empty(
+    store: &Arc<dyn ObjectStore>,
+    path: &ObjectPath,
+) -> Result<(Vec<UsageIntervalEntity>, Option<UpdateVersion>)> {
+    match store.get(path).await {
+        Ok(result) => {
+            let version = UpdateVersion {
+                e_tag: result.meta.e_tag.clone(),
+                version: result.meta.version.clone(),
+            };
+            let bytes = result.bytes().await?;
+            Ok((parse_lines(&bytes), Some(version)))
+        }
+        Err(ObjectStoreError::NotFound { .. }) => Ok((Vec::new(), None)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn parse_lines(bytes: &[u8]) -> Vec<UsageIntervalEntity> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .filter_map(|line| match serde_json::from_str::<UsageIntervalEntity>(line) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                warn!("Found illegal json string {line}: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Builds an [ObjectStoreRecordStorage] from a `--object-store-url` like `s3://bucket/prefix`,
+/// so selecting the backend is a single CLI flag instead of wiring up credentials/config by hand.
+pub fn from_url(url: &str) -> Result<ObjectStoreRecordStorage> {
+    let parsed = Url::parse(url).with_context(|| format!("Invalid object store URL {url:?}"))?;
+    let (store, prefix) = object_store::parse_url(&parsed)
+        .with_context(|| format!("Failed to resolve object store URL {url:?}"))?;
+    Ok(ObjectStoreRecordStorage::new(Arc::from(store), prefix))
+}
+
+/// Selects between the filesystem and object-store [RecordStorage] backends at runtime, so the
+/// daemon's processing pipeline doesn't need to be generic over the backend just to support
+/// `--object-store-url`.
+pub enum RecordStorageBackend {
+    Local(RecordStorageImpl),
+    ObjectStore(ObjectStoreRecordStorage),
+}
+
+/// The [RecordFileHandle] counterpart to [RecordStorageBackend].
+pub enum RecordFileBackend {
+    Local(<RecordStorageImpl as RecordStorage>::RecordFile),
+    ObjectStore(ObjectStoreRecordFile),
+}
+
+impl RecordFileHandle for RecordFileBackend {
+    async fn append(&mut self, usage_records: Vec<UsageRecordEntity>) -> Result<()> {
+        match self {
+            Self::Local(file) => file.append(usage_records).await,
+            Self::ObjectStore(file) => file.append(usage_records).await,
+        }
+    }
+
+    fn get_date(&self) -> NaiveDate {
+        match self {
+            Self::Local(file) => file.get_date(),
+            Self::ObjectStore(file) => file.get_date(),
+        }
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::Local(file) => file.flush().await,
+            Self::ObjectStore(file) => file.flush().await,
+        }
+    }
+}
+
+impl RecordStorage for RecordStorageBackend {
+    type RecordFile = RecordFileBackend;
+
+    async fn create_or_append_record(&self, date: NaiveDate) -> Result<Self::RecordFile> {
+        match self {
+            Self::Local(storage) => storage.create_or_append_record(date).await.map(RecordFileBackend::Local),
+            Self::ObjectStore(storage) => {
+                storage.create_or_append_record(date).await.map(RecordFileBackend::ObjectStore)
+            }
+        }
+    }
+
+    async fn get_data_for(&self, date: NaiveDate) -> Result<Vec<UsageIntervalEntity>> {
+        match self {
+            Self::Local(storage) => storage.get_data_for(date).await,
+            Self::ObjectStore(storage) => storage.get_data_for(date).await,
+        }
+    }
+
+    async fn stream_data_for(
+        &self,
+        date: NaiveDate,
+    ) -> Result<impl Stream<Item = Result<UsageIntervalEntity>> + Send + 'static> {
+        Ok(match self {
+            Self::Local(storage) => storage.stream_data_for(date).await?.boxed(),
+            Self::ObjectStore(storage) => storage.stream_data_for(date).await?.boxed(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use chrono::{Duration, TimeZone, Utc};
+    use object_store::memory::InMemory;
+
+    use crate::daemon::storage::entities::UsageRecordEntity;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn flush_retries_after_a_concurrent_writer_commits_first() -> Result<()> {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let prefix = ObjectPath::from("records");
+        let storage = ObjectStoreRecordStorage::new(store.clone(), prefix.clone());
+
+        let date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().date_naive();
+        let mut file = storage.create_or_append_record(date).await?;
+
+        file.append(vec![UsageRecordEntity {
+            window_name: "our-window".into(),
+            process_name: "our-process".into(),
+            moment: Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap(),
+            afk: false,
+        }])
+        .await?;
+
+        // Simulate a concurrent writer committing its own interval to the same object in between
+        // our initial GET (inside `create_or_append_record`) and our `flush`.
+        let concurrent = UsageIntervalEntity {
+            window_name: "other-window".into(),
+            process_name: "other-process".into(),
+            start: Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap(),
+            duration: Duration::minutes(1),
+            afk: false,
+        };
+        let mut buffer = Vec::new();
+        serde_json::to_writer(&mut buffer, &concurrent)?;
+        buffer.push(b'\n');
+        store.put(&prefix.child(date_to_record_name(date)), Bytes::from(buffer).into()).await?;
+
+        // The first PUT attempt races the concurrent writer's commit and should hit
+        // Precondition/AlreadyExists, forcing a re-GET + merge before it can succeed.
+        file.flush().await?;
+
+        let committed = storage.get_data_for(date).await?;
+        assert!(
+            committed.iter().any(|i| i.process_name.as_ref() == "other-process"),
+            "concurrent writer's interval should survive the retry's merge: {committed:?}"
+        );
+        assert!(
+            committed.iter().any(|i| i.process_name.as_ref() == "our-process"),
+            "our own interval should still be flushed: {committed:?}"
+        );
+
+        Ok(())
+    }
+}
@@ -8,16 +8,20 @@ use std::{
 use anyhow::Result;
 use chrono::{Duration, NaiveDate};
 use fs4::tokio::AsyncFileExt;
+use futures::{Stream, StreamExt, future, stream};
 use tokio::{
     fs::File,
     io::{
         AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite,
-        AsyncWriteExt, BufReader,
+        AsyncWriteExt, BufReader, Lines,
     },
 };
 use tracing::{debug, warn};
 
-use crate::{fs::operations::seek_line_backwards, utils::time::date_to_record_name};
+use crate::{
+    fs::operations::seek_line_backwards,
+    utils::time::{date_to_record_name, record_name_to_date},
+};
 
 use super::entities::{UsageIntervalEntity, UsageRecordEntity};
 
@@ -37,6 +41,39 @@ pub trait RecordStorage {
         &self,
         date: NaiveDate,
     ) -> impl Future<Output = Result<Vec<UsageIntervalEntity>>> + Send;
+
+    /// Streams data from a record file for a certain day. [RecordStorageImpl] parses the file line
+    /// by line without ever buffering the whole day in memory; the object-store backend fetches
+    /// and parses the whole day's object before yielding from it. Intended for report code that
+    /// folds over large ranges incrementally.
+    fn stream_data_for(
+        &self,
+        date: NaiveDate,
+    ) -> impl Future<Output = Result<impl Stream<Item = Result<UsageIntervalEntity>> + Send + 'static>> + Send;
+
+    /// Lazily chains [RecordStorage::stream_data_for] over every day between `from` and `to`
+    /// (inclusive). Each day's file is only opened once the stream reaches that day, so a
+    /// multi-month range never holds more than a day's worth of data in memory.
+    fn stream_range(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Stream<Item = Result<UsageIntervalEntity>> + Send
+    where
+        Self: Sized + Sync,
+    {
+        stream::iter(date_range(from, to))
+            .then(move |date| self.stream_data_for(date))
+            .flat_map(|result| match result {
+                Ok(stream) => stream.boxed(),
+                Err(e) => stream::once(future::ready(Err(e))).boxed(),
+            })
+    }
+}
+
+/// Returns an iterator of dates between `from` (inclusive) and `to` (inclusive).
+fn date_range(from: NaiveDate, to: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    std::iter::successors(Some(from), |date| date.succ_opt()).take_while(move |date| *date <= to)
 }
 
 impl<T: Deref> RecordStorage for T
@@ -58,15 +95,38 @@ where
     ) -> impl Future<Output = Result<Vec<UsageIntervalEntity>>> + Send {
         self.deref().get_data_for(date)
     }
+
+    fn stream_data_for(
+        &self,
+        date: NaiveDate,
+    ) -> impl Future<Output = Result<impl Stream<Item = Result<UsageIntervalEntity>> + Send + 'static>> + Send
+    {
+        self.deref().stream_data_for(date)
+    }
 }
 
 pub trait RecordFileHandle {
     fn append(&mut self, usage_records: Vec<UsageRecordEntity>)
     -> impl Future<Output = Result<()>>;
     fn get_date(&self) -> NaiveDate;
+    /// fsyncs the file to disk. `append` already writes every record through to the OS on its own,
+    /// so this only matters for surviving a power loss/OS crash between appends, not a process
+    /// crash.
     fn flush(&mut self) -> impl Future<Output = Result<()>>;
 }
 
+/// Lets [UsageIntervalRecordFile::flush] fsync regardless of the concrete file type `F`, instead of
+/// depending on `tokio::fs::File` directly.
+pub trait AsyncFileSync {
+    fn sync_all(&self) -> impl Future<Output = std::io::Result<()>>;
+}
+
+impl AsyncFileSync for File {
+    async fn sync_all(&self) -> std::io::Result<()> {
+        File::sync_all(self).await
+    }
+}
+
 /// The main realization of [RecordStorage].
 pub struct RecordStorageImpl {
     record_dir: PathBuf,
@@ -142,6 +202,135 @@ impl RecordStorage for RecordStorageImpl {
         let data = self.get_all_inner(&path).await?;
         Ok(data)
     }
+
+    async fn stream_data_for(
+        &self,
+        date: chrono::NaiveDate,
+    ) -> Result<impl Stream<Item = Result<UsageIntervalEntity>> + Send + 'static> {
+        let file_name = date_to_record_name(date);
+        let path = self.record_dir.join(file_name);
+        open_line_stream(path).await
+    }
+}
+
+/// Bounds how much a [RecordStorageImpl]'s record directory is allowed to grow. Either bound can
+/// be left unset to only enforce the other one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    /// Files for days older than this are deleted.
+    pub max_age: Option<Duration>,
+    /// Oldest files are deleted until the directory is at or under this many bytes.
+    pub max_total_bytes: Option<u64>,
+}
+
+impl RecordStorageImpl {
+    /// Enforces `policy` against `record_dir`, deleting the oldest record files first until both
+    /// the age cutoff and the byte budget are satisfied.
+    ///
+    /// The file for today, and any file currently being appended to by another handle, are never
+    /// deleted: a file is only considered for deletion once a non-blocking exclusive lock on it
+    /// can be acquired. Files whose name doesn't parse as a date (from [record_name_to_date]) are
+    /// left untouched, since they aren't ours to manage.
+    pub async fn enforce_retention(&self, policy: RetentionPolicy) -> Result<()> {
+        let today = chrono::Utc::now().date_naive();
+
+        let mut entries = Vec::new();
+        let mut read_dir = tokio::fs::read_dir(&self.record_dir).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+            let Some(date) = record_name_to_date(&name) else {
+                continue;
+            };
+            if date == today {
+                continue;
+            }
+            let size = entry.metadata().await?.len();
+            entries.push((date, entry.path(), size));
+        }
+
+        entries.sort_by_key(|(date, _, _)| *date);
+
+        let mut total_bytes: u64 = entries.iter().map(|(_, _, size)| size).sum();
+
+        for (date, path, size) in entries {
+            let age_exceeded = policy
+                .max_age
+                .is_some_and(|max_age| today - date > max_age);
+            let over_budget = policy
+                .max_total_bytes
+                .is_some_and(|max_bytes| total_bytes > max_bytes);
+
+            if !age_exceeded && !over_budget {
+                break;
+            }
+
+            let file = match File::options().write(true).open(&path).await {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            // A file still being actively appended to is locked exclusively by its writer, so a
+            // failed non-blocking lock attempt means "still in use" rather than "eligible".
+            if file.try_lock_exclusive().is_err() {
+                debug!("Skipping retention for {path:?}, file is still in use");
+                continue;
+            }
+            file.unlock_async().await?;
+            drop(file);
+
+            tokio::fs::remove_file(&path).await?;
+            total_bytes -= size;
+        }
+
+        Ok(())
+    }
+}
+
+/// Opens a record file and lazily parses it line by line, yielding intervals as they're read
+/// instead of buffering the whole day. A missing file simply yields nothing, and malformed lines
+/// are logged and skipped, same tolerance as [RecordStorageImpl::get_all_inner].
+async fn open_line_stream(
+    path: PathBuf,
+) -> Result<impl Stream<Item = Result<UsageIntervalEntity>> + Send + 'static> {
+    let file = match File::open(&path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(stream::empty().boxed()),
+        Err(e) => return Err(e.into()),
+    };
+    file.lock_shared()?;
+    let lines = BufReader::new(file).lines();
+
+    Ok(stream::unfold(Some(lines), move |lines| {
+        let path = path.clone();
+        async move {
+            let mut lines: Lines<BufReader<File>> = lines?;
+            loop {
+                match lines.next_line().await {
+                    Ok(Some(line)) => match serde_json::from_str::<UsageIntervalEntity>(&line) {
+                        Ok(v) => return Some((Ok(v), Some(lines))),
+                        Err(e) => {
+                            // ignore illegal values. Might happen after shutdowns
+                            warn!(
+                                "During parsing in path {:?} found illegal json string {}:  {e}",
+                                path, &line
+                            )
+                        }
+                    },
+                    Ok(None) => {
+                        return match lines.into_inner().into_inner().unlock_async().await {
+                            Ok(()) => None,
+                            Err(e) => Some((Err(e.into()), None)),
+                        };
+                    }
+                    Err(e) => return Some((Err(e.into()), None)),
+                }
+            }
+        }
+    })
+    .boxed())
 }
 
 pub struct UsageIntervalRecordFile<F> {
@@ -149,8 +338,8 @@ pub struct UsageIntervalRecordFile<F> {
     date: NaiveDate,
 }
 
-impl<F: AsyncSeek + AsyncRead + AsyncWrite + fs4::tokio::AsyncFileExt + Unpin> RecordFileHandle
-    for UsageIntervalRecordFile<F>
+impl<F: AsyncSeek + AsyncRead + AsyncWrite + fs4::tokio::AsyncFileExt + AsyncFileSync + Unpin>
+    RecordFileHandle for UsageIntervalRecordFile<F>
 {
     async fn append(&mut self, usage_record: Vec<UsageRecordEntity>) -> Result<()> {
         self.append_inner(usage_record.clone()).await
@@ -161,11 +350,12 @@ impl<F: AsyncSeek + AsyncRead + AsyncWrite + fs4::tokio::AsyncFileExt + Unpin> R
     }
 
     async fn flush(&mut self) -> Result<()> {
+        self.file.sync_all().await?;
         Ok(())
     }
 }
 
-impl<F: AsyncSeek + AsyncRead + AsyncWrite + fs4::tokio::AsyncFileExt + Unpin>
+impl<F: AsyncSeek + AsyncRead + AsyncWrite + fs4::tokio::AsyncFileExt + AsyncFileSync + Unpin>
     UsageIntervalRecordFile<F>
 {
     fn new(file: F, date: NaiveDate) -> Self {
@@ -235,7 +425,7 @@ impl<F: AsyncSeek + AsyncRead + AsyncWrite + fs4::tokio::AsyncFileExt + Unpin>
 const MAX_MERGE_DURATION: Duration = Duration::seconds(2);
 
 /// Creates an optimal sequence of intervals.
-fn collapse_records(
+pub(crate) fn collapse_records(
     last_interval: Option<UsageIntervalEntity>,
     usage_records: impl IntoIterator<Item = UsageRecordEntity>,
 ) -> Vec<UsageIntervalEntity> {
@@ -276,6 +466,7 @@ mod tests {
 
     use anyhow::Result;
     use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+    use futures::{StreamExt, TryStreamExt};
     use tempfile::{tempdir, tempfile};
     use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
@@ -568,4 +759,107 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_stream_data_for_matches_get_data_for() -> Result<()> {
+        let dir = tempdir()?;
+        let storage = RecordStorageImpl::new(dir.path().to_owned())?;
+        let mut record = storage.create_or_append_record(TEST_START_DATE.date()).await?;
+        record
+            .append_inner(vec![UsageRecordEntity {
+                window_name: "test".into(),
+                process_name: "test process".into(),
+                moment: Utc.from_utc_datetime(&TEST_START_DATE),
+                afk: false,
+            }])
+            .await?;
+        record.flush().await?;
+
+        let buffered = storage.get_data_for(TEST_START_DATE.into()).await?;
+        let streamed: Vec<_> = storage
+            .stream_data_for(TEST_START_DATE.into())
+            .await?
+            .try_collect()
+            .await?;
+
+        assert_eq!(buffered, streamed);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_data_for_missing_day_is_empty() -> Result<()> {
+        let dir = tempdir()?;
+        let storage = RecordStorageImpl::new(dir.path().to_owned())?;
+
+        let streamed: Vec<_> = storage
+            .stream_data_for(TEST_START_DATE.into())
+            .await?
+            .try_collect()
+            .await?;
+
+        assert!(streamed.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_range_chains_days() -> Result<()> {
+        let dir = tempdir()?;
+        let storage = RecordStorageImpl::new(dir.path().to_owned())?;
+
+        let first_day = TEST_START_DATE.date();
+        let second_day = first_day.succ_opt().unwrap();
+
+        for day in [first_day, second_day] {
+            let mut record = storage.create_or_append_record(day).await?;
+            record
+                .append_inner(vec![UsageRecordEntity {
+                    window_name: "test".into(),
+                    process_name: "test process".into(),
+                    moment: Utc.from_utc_datetime(&NaiveDateTime::new(day, NaiveTime::MIN)),
+                    afk: false,
+                }])
+                .await?;
+            record.flush().await?;
+        }
+
+        // The third day has no file and should simply be skipped.
+        let ranged: Vec<_> = storage
+            .stream_range(first_day, second_day.succ_opt().unwrap())
+            .try_collect()
+            .await?;
+
+        assert_eq!(ranged.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_enforce_retention_deletes_old_files_but_not_today() -> Result<()> {
+        use super::RetentionPolicy;
+
+        let dir = tempdir()?;
+        let storage = RecordStorageImpl::new(dir.path().to_owned())?;
+
+        let today = Utc::now().date_naive();
+        let old_day = today - Duration::days(10);
+
+        for day in [old_day, today] {
+            let mut record = storage.create_or_append_record(day).await?;
+            record.flush().await?;
+        }
+
+        storage
+            .enforce_retention(RetentionPolicy {
+                max_age: Some(Duration::days(1)),
+                max_total_bytes: None,
+            })
+            .await?;
+
+        assert!(dir.path().join(crate::utils::time::date_to_record_name(today)).exists());
+        assert!(!dir.path().join(crate::utils::time::date_to_record_name(old_day)).exists());
+
+        Ok(())
+    }
 }
@@ -74,6 +74,18 @@ fn run_service(command_args: Vec<String>) -> Result<()> {
 fn run(args: DaemonArgs) -> Result<()> {
     let app_dir = args.dir.map_or_else(create_application_default_path, Ok)?;
     enable_logging(DAEMON_PREFIX, &app_dir.join("logs"), args.log, args.log_console).unwrap();
-    single_thread_runtime()?.block_on(async move { start_daemon(app_dir).await })?;
+    single_thread_runtime()?.block_on(async move {
+        start_daemon(
+            app_dir,
+            args.notify,
+            args.notify_budget,
+            args.collection_interval_secs,
+            args.afk_threshold_secs,
+            args.object_store_url,
+            args.retention_max_age_days,
+            args.retention_max_bytes,
+        )
+        .await
+    })?;
     Ok(())
 }
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite};
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
 
 /// Moves backwards in a file to beginning of a previous line.
 /// Useful if you wan't to overwrite last line with new data.
@@ -36,6 +36,43 @@ pub async fn seek_line_backwards(
     }
 }
 
+/// Seeks `file` back past `lines` newline boundaries from its current position (typically
+/// end-of-file), by repeatedly calling [seek_line_backwards]. Leaves the file positioned at the
+/// start of what the caller should treat as "the tail" once read forward to EOF.
+pub async fn seek_last_lines(
+    file: &mut (impl AsyncSeek + AsyncWrite + AsyncRead + Unpin),
+    lines: usize,
+    buffer: &mut [u8],
+) -> Result<(), io::Error> {
+    for _ in 0..lines {
+        seek_line_backwards(file, buffer).await?;
+    }
+    Ok(())
+}
+
+/// Performs a single `tail -f`-style poll tick: if `file` has grown past `position`, the new
+/// bytes are copied to `output`; if it has shrunk (the log file was rotated/truncated since the
+/// last tick), reading resumes from the start instead. Returns the read position to pass back in
+/// on the next tick. Callers drive this in a loop with a sleep in between.
+pub async fn follow_file_once(
+    file: &mut (impl AsyncSeek + AsyncRead + Unpin),
+    position: u64,
+    mut output: impl AsyncWrite + Unpin,
+) -> Result<u64, io::Error> {
+    let len = file.seek(std::io::SeekFrom::End(0)).await?;
+    let position = if len < position { 0 } else { position };
+
+    if len > position {
+        file.seek(std::io::SeekFrom::Start(position)).await?;
+        let mut buffer = vec![0u8; (len - position) as usize];
+        file.read_exact(&mut buffer).await?;
+        output.write_all(&buffer).await?;
+        output.flush().await?;
+    }
+
+    Ok(len)
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Write;
@@ -43,9 +80,9 @@ mod tests {
     use anyhow::Result;
 
     use tempfile::tempfile;
-    use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
+    use tokio::io::{AsyncBufReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
 
-    use crate::fs::operations::seek_line_backwards;
+    use crate::fs::operations::{follow_file_once, seek_line_backwards};
 
     #[tokio::test]
     async fn test_seek_line_backwards_basic() -> Result<()> {
@@ -225,4 +262,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_follow_file_once_resumes_from_zero_after_rotation() -> Result<()> {
+        let mut file = tokio::fs::File::from_std(tempfile()?);
+
+        file.write_all(b"first\n").await?;
+        let mut output = Vec::new();
+        let position = follow_file_once(&mut file, 0, &mut output).await?;
+        assert_eq!(output, b"first\n");
+        assert_eq!(position, 6);
+
+        // Simulate a log rotation/truncation: the file is now shorter than `position`.
+        file.set_len(0).await?;
+        file.seek(std::io::SeekFrom::Start(0)).await?;
+        file.write_all(b"hi\n").await?;
+
+        let mut output = Vec::new();
+        let position = follow_file_once(&mut file, position, &mut output).await?;
+        assert_eq!(output, b"hi\n", "should resume from the start instead of erroring or skipping the new content");
+        assert_eq!(position, 3);
+
+        Ok(())
+    }
 }
@@ -17,6 +17,7 @@ pub trait Clock: Sync + Send + 'static {
     async fn sleep_until(&self, instant: tokio::time::Instant);
 }
 
+#[derive(Clone, Copy)]
 pub struct DefaultClock;
 
 #[async_trait]
@@ -37,3 +38,49 @@ impl Clock for DefaultClock {
         tokio::time::sleep_until(instant).await;
     }
 }
+
+/// A [Clock] anchored to a `tokio::time::Instant` instead of [Utc::now], so it moves in lock-step
+/// with tokio's paused-time machinery. `time()` is computed as `anchor_utc + (Instant::now() -
+/// anchor_instant)`: since `Instant::now()` is controllable under `tokio::time::pause`/`advance`
+/// (and auto-advances to the next scheduled timer once the runtime is otherwise idle), this lets
+/// the whole collector/processor pipeline be driven through simulated days in milliseconds of real
+/// wall-clock time instead of real sleeps.
+///
+/// This only works end to end if every wait in the pipeline goes through [Clock] rather than bare
+/// `tokio::time` or `Utc::now` directly — auto-advance only ever jumps forward to the next
+/// *pending* timer, so a wait it doesn't know about will never be woken.
+#[derive(Clone)]
+pub struct AnchoredClock {
+    anchor_utc: DateTime<Utc>,
+    anchor_instant: Instant,
+}
+
+impl AnchoredClock {
+    pub fn new(anchor_utc: DateTime<Utc>) -> Self {
+        Self {
+            anchor_utc,
+            anchor_instant: Instant::now(),
+        }
+    }
+}
+
+#[async_trait]
+impl Clock for AnchoredClock {
+    fn time(&self) -> DateTime<Utc> {
+        let elapsed = chrono::Duration::from_std(Instant::now() - self.anchor_instant)
+            .expect("elapsed time since anchor should always fit in a chrono::Duration");
+        self.anchor_utc + elapsed
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+
+    async fn sleep_until(&self, instant: tokio::time::Instant) {
+        tokio::time::sleep_until(instant).await;
+    }
+}
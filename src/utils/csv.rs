@@ -0,0 +1,27 @@
+/// Quotes `value` if it contains a character that would otherwise corrupt CSV row structure —
+/// the field/row delimiters `,`, `"`, `\n`, and `\r` — doubling any inner quotes per RFC 4180.
+pub fn escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_quotes_only_when_needed() {
+        assert_eq!(escape("firefox"), "firefox");
+        assert_eq!(escape("a,b"), "\"a,b\"");
+        assert_eq!(escape(r#"say "hi""#), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn escape_quotes_embedded_newlines_and_carriage_returns() {
+        assert_eq!(escape("line1\nline2"), "\"line1\nline2\"");
+        assert_eq!(escape("line1\r\nline2"), "\"line1\r\nline2\"");
+    }
+}
@@ -1,31 +1,15 @@
 use std::{env, io, path::PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result, anyhow};
+
+/// Env var that, when set, overrides the per-OS default storage location entirely. Useful for
+/// pointing storage at a synced folder, or at a test fixture.
+const DATA_DIR_OVERRIDE: &str = "WHATAWHAT_DATA_DIR";
 
 pub fn create_application_default_path() -> Result<PathBuf> {
-    let path = {
-        #[cfg(windows)]
-        {
-            let mut path =
-                PathBuf::from(env::var("APPDATA").expect("APPDATA should be present on Windows"));
-            path.push("whatawhat");
-            path
-        }
-        #[cfg(target_os = "linux")]
-        {
-            let mut path = env::var("XDG_STATE_HOME")
-                .map(PathBuf::from)
-                .or_else(|_| {
-                    env::var("HOME").map(|home| {
-                        let mut path = PathBuf::from(home);
-                        path.push(".local/state");
-                        path
-                    })
-                })
-                .expect("Couldn't find neither XDG_STATE_HOME nor HOME");
-            path.push("whatawhat");
-            path
-        }
+    let path = match env::var(DATA_DIR_OVERRIDE) {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => default_application_path()?,
     };
 
     match std::fs::create_dir_all(&path) {
@@ -34,3 +18,37 @@ pub fn create_application_default_path() -> Result<PathBuf> {
         Err(v) => Err(v.into()),
     }
 }
+
+fn default_application_path() -> Result<PathBuf> {
+    #[cfg(windows)]
+    {
+        let mut path = PathBuf::from(
+            env::var("APPDATA").context("APPDATA should be present on Windows")?,
+        );
+        path.push("whatawhat");
+        Ok(path)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let mut path = env::var("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|_| {
+                env::var("HOME").map(|home| {
+                    let mut path = PathBuf::from(home);
+                    path.push(".local/state");
+                    path
+                })
+            })
+            .map_err(|_| anyhow!("Couldn't find neither XDG_STATE_HOME nor HOME"))?;
+        path.push("whatawhat");
+        Ok(path)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let home = env::var("HOME").context("HOME should be present on macOS")?;
+        let mut path = PathBuf::from(home);
+        path.push("Library/Application Support");
+        path.push("whatawhat");
+        Ok(path)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod clock;
+pub mod csv;
+pub mod dir;
+pub mod logging;
+pub mod percentage;
+pub mod runtime;
+pub mod time;
@@ -7,6 +7,12 @@ pub fn date_to_record_name(date: NaiveDate) -> String {
     date.format("%Y-%m-%d").to_string()
 }
 
+/// Inverse of [date_to_record_name]. Returns `None` for names that aren't in the expected format,
+/// which includes unrelated files that might end up in the record directory.
+pub fn record_name_to_date(name: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(name, "%Y-%m-%d").ok()
+}
+
 /// Returns start of the next day.
 pub fn next_day_start<Tz: TimeZone>(date: DateTime<Tz>) -> DateTime<Tz> {
     (date + Duration::days(1)).with_time(NaiveTime::MIN).unwrap()
@@ -1,5 +1,7 @@
-use anyhow::Result;
-use sysinfo::Pid;
+use std::{env, fs, process::Command};
+
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
 use tracing::instrument;
 use xcb::{
     Connection,
@@ -35,16 +37,31 @@ fn get_pid(conn: &Connection, window: Window, pid_atom: Atom) -> Result<Option<u
     Ok(Some(result_slice[0]))
 }
 
-fn get_process_name(id: u32) -> Result<Option<String>> {
-    let system = sysinfo::System::new_all();
-    let Some(process) = system.process(Pid::from_u32(id)) else {
-        return Ok(None);
-    };
+/// Resolves a PID to its owning executable, preferring the resolved `/proc/<pid>/exe` symlink
+/// (the full path Windows also reports), then falling back to `/proc/<pid>/comm` and finally
+/// `/proc/<pid>/cmdline` for processes whose `exe` link can't be read (e.g. permission, already
+/// exited, or a container boundary).
+fn get_process_name(pid: u32) -> Option<String> {
+    if let Ok(exe) = fs::read_link(format!("/proc/{pid}/exe")) {
+        if let Some(path) = exe.to_str() {
+            return Some(path.to_string());
+        }
+    }
 
-    Ok(process
-        .exe()
-        .and_then(|v| v.to_str())
-        .map(|v| v.to_string()))
+    if let Ok(comm) = fs::read_to_string(format!("/proc/{pid}/comm")) {
+        let comm = comm.trim();
+        if !comm.is_empty() {
+            return Some(comm.to_string());
+        }
+    }
+
+    if let Ok(cmdline) = fs::read_to_string(format!("/proc/{pid}/cmdline")) {
+        if let Some(argv0) = cmdline.split('\0').next().filter(|v| !v.is_empty()) {
+            return Some(argv0.to_string());
+        }
+    }
+
+    None
 }
 
 fn get_active_window_atom(conn: &Connection) -> Result<Atom> {
@@ -75,7 +92,69 @@ fn get_net_wm_name_atom(conn: &Connection) -> Result<Atom> {
     Ok(response.atom())
 }
 
-pub fn get_name(conn: &Connection, window: Window, wm_name_atom: Atom) -> Result<String> {
+/// Legacy ICCCM title property, consulted when `_NET_WM_NAME` is unset (older or minimal apps).
+fn get_wm_name_atom(conn: &Connection) -> Result<Atom> {
+    let response = conn.wait_for_reply(conn.send_request(&InternAtom {
+        only_if_exists: false,
+        name: b"WM_NAME",
+    }))?;
+    Ok(response.atom())
+}
+
+fn get_utf8_string_atom(conn: &Connection) -> Result<Atom> {
+    let response = conn.wait_for_reply(conn.send_request(&InternAtom {
+        only_if_exists: false,
+        name: b"UTF8_STRING",
+    }))?;
+    Ok(response.atom())
+}
+
+fn get_compound_text_atom(conn: &Connection) -> Result<Atom> {
+    let response = conn.wait_for_reply(conn.send_request(&InternAtom {
+        only_if_exists: false,
+        name: b"COMPOUND_TEXT",
+    }))?;
+    Ok(response.atom())
+}
+
+/// Decodes a `STRING` property (ICCCM Latin-1) without risking a panic on bytes that aren't valid
+/// UTF-8: each byte is its own Latin-1 code point, which maps 1:1 onto the first 256 Unicode
+/// scalar values.
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Best-effort decoding for `COMPOUND_TEXT`, which can contain ISO-2022 escape sequences for
+/// switching charsets. We don't implement the full ISO-2022 state machine; instead we drop the
+/// control/escape bytes and decode whatever ASCII/Latin-1 text remains, which covers the common
+/// case of apps that never actually switch out of the default charset.
+fn decode_compound_text_lossy(bytes: &[u8]) -> String {
+    decode_latin1(bytes)
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .collect()
+}
+
+pub fn get_name(
+    conn: &Connection,
+    window: Window,
+    net_wm_name_atom: Atom,
+    wm_name_atom: Atom,
+    utf8_string_atom: Atom,
+    compound_text_atom: Atom,
+) -> Result<String> {
+    let net_wm_name = conn.wait_for_reply(conn.send_request(&x::GetProperty {
+        delete: false,
+        window,
+        property: net_wm_name_atom,
+        r#type: utf8_string_atom,
+        long_offset: 0,
+        long_length: 1024,
+    }))?;
+    if !net_wm_name.value::<u8>().is_empty() {
+        return Ok(String::from_utf8_lossy(net_wm_name.value::<u8>()).into_owned());
+    }
+
     let wm_name = conn.wait_for_reply(conn.send_request(&x::GetProperty {
         delete: false,
         window,
@@ -84,30 +163,53 @@ pub fn get_name(conn: &Connection, window: Window, wm_name_atom: Atom) -> Result
         long_offset: 0,
         long_length: 1024,
     }))?;
-    let title = String::from_utf8(wm_name.value().to_vec())
-        .expect("The WM_NAME property is not valid UTF-8");
-    Ok(title)
+    let bytes = wm_name.value::<u8>();
+    if bytes.is_empty() {
+        return Ok(String::new());
+    }
+
+    let property_type = wm_name.r#type();
+    if property_type == utf8_string_atom {
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    } else if property_type == x::ATOM_STRING {
+        Ok(decode_latin1(bytes))
+    } else if property_type == compound_text_atom {
+        Ok(decode_compound_text_lossy(bytes))
+    } else {
+        // Unknown encoding: fall back to a lossy UTF-8 decode rather than panicking or dropping
+        // the title entirely.
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
 }
 
-pub struct LinuxWindowManager {
+struct X11WindowManager {
     connection: Connection,
     preferred_screen: i32,
     active_window_atom: Atom,
-    window_name_atom: Atom,
+    net_wm_name_atom: Atom,
+    wm_name_atom: Atom,
+    utf8_string_atom: Atom,
+    compound_text_atom: Atom,
     pid_atom: Atom,
 }
 
-impl LinuxWindowManager {
-    pub fn new() -> Result<Self> {
+impl X11WindowManager {
+    fn new() -> Result<Self> {
         let (connection, preferred_screen) = xcb::Connection::connect(None)?;
         let active_window_atom = get_active_window_atom(&connection)?;
-        let name_atom = get_net_wm_name_atom(&connection)?;
+        let net_wm_name_atom = get_net_wm_name_atom(&connection)?;
+        let wm_name_atom = get_wm_name_atom(&connection)?;
+        let utf8_string_atom = get_utf8_string_atom(&connection)?;
+        let compound_text_atom = get_compound_text_atom(&connection)?;
         let pid_atom = get_pid_atom(&connection)?;
         Ok(Self {
             connection,
             preferred_screen,
             active_window_atom,
-            window_name_atom: name_atom,
+            net_wm_name_atom,
+            wm_name_atom,
+            utf8_string_atom,
+            compound_text_atom,
             pid_atom,
         })
     }
@@ -121,9 +223,18 @@ impl LinuxWindowManager {
 
         let active_window =
             get_active_window(&self.connection, &default_window, self.active_window_atom)?;
-        let window_name = get_name(&self.connection, active_window, self.window_name_atom)?;
-        let process = get_pid(&self.connection, active_window, self.pid_atom)?.unwrap();
-        let process_name = get_process_name(process)?.unwrap();
+        let window_name = get_name(
+            &self.connection,
+            active_window,
+            self.net_wm_name_atom,
+            self.wm_name_atom,
+            self.utf8_string_atom,
+            self.compound_text_atom,
+        )?;
+        let process = get_pid(&self.connection, active_window, self.pid_atom)?
+            .ok_or_else(|| anyhow!("Active window has no _NET_WM_PID property"))?;
+        let process_name = get_process_name(process)
+            .ok_or_else(|| anyhow!("Failed to resolve executable for pid {process}"))?;
         Ok(ActiveWindowData {
             window_title: window_name.into(),
             process_name: process_name.into(),
@@ -131,7 +242,7 @@ impl LinuxWindowManager {
     }
 }
 
-impl WindowManager for LinuxWindowManager {
+impl WindowManager for X11WindowManager {
     #[instrument(skip(self))]
     fn get_active_window_data(&mut self) -> Result<ActiveWindowData> {
         assert!(self.preferred_screen >= 0);
@@ -158,3 +269,140 @@ impl WindowManager for LinuxWindowManager {
         Ok(reply.ms_since_user_input())
     }
 }
+
+/// Best-effort fallback for pure-Wayland sessions, where there's no standardized protocol for
+/// reading the focused window or idle time. Currently only sway/wlroots compositors are supported,
+/// via their `swaymsg` IPC tool.
+struct WaylandWindowManager;
+
+impl WaylandWindowManager {
+    fn new() -> Self {
+        Self
+    }
+}
+
+fn find_focused_container(node: &Value) -> Option<&Value> {
+    if node.get("focused").and_then(Value::as_bool) == Some(true) {
+        return Some(node);
+    }
+
+    node.get("nodes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .chain(
+            node.get("floating_nodes")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten(),
+        )
+        .find_map(find_focused_container)
+}
+
+impl WindowManager for WaylandWindowManager {
+    #[instrument(skip(self))]
+    fn get_active_window_data(&mut self) -> Result<ActiveWindowData> {
+        let output = Command::new("swaymsg")
+            .args(["-t", "get_tree"])
+            .output()
+            .context("Failed to query swaymsg (the Wayland fallback currently only supports sway/wlroots compositors)")?;
+
+        let tree: Value =
+            serde_json::from_slice(&output.stdout).context("Failed to parse swaymsg output")?;
+        let focused = find_focused_container(&tree)
+            .ok_or_else(|| anyhow!("swaymsg reported no focused window"))?;
+
+        let window_title = focused
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let process_name = focused
+            .get("pid")
+            .and_then(Value::as_u64)
+            .and_then(|pid| get_process_name(pid as u32))
+            .unwrap_or_else(|| window_title.clone());
+
+        Ok(ActiveWindowData {
+            window_title: window_title.into(),
+            process_name: process_name.into(),
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn get_idle_time(&mut self) -> Result<u32> {
+        // There's no standard cross-compositor protocol for idle time under Wayland, so we can't
+        // populate this the way the X11 screensaver extension and Windows' GetLastInputInfo do.
+        // Treat the session as always-active rather than erroring: collect_data's `?` would
+        // otherwise abort every single tick before a RecordEvent is ever built, which would make
+        // this whole fallback a no-op for the users it's meant to help.
+        Ok(0)
+    }
+}
+
+/// Picks X11 if a display server is reachable, otherwise falls back to the best-effort Wayland
+/// backend. This mirrors what desktop environments themselves do during the X11-to-Wayland
+/// transition: most X11 apps (and this xcb connection) keep working under XWayland, so we only
+/// fall back when there's truly no X11 to talk to.
+pub enum LinuxWindowManager {
+    X11(X11WindowManager),
+    Wayland(WaylandWindowManager),
+}
+
+impl LinuxWindowManager {
+    pub fn new() -> Result<Self> {
+        match X11WindowManager::new() {
+            Ok(manager) => Ok(Self::X11(manager)),
+            Err(e) if env::var_os("WAYLAND_DISPLAY").is_some() => {
+                tracing::warn!("Falling back to the Wayland window manager backend: {e:?}");
+                Ok(Self::Wayland(WaylandWindowManager::new()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl WindowManager for LinuxWindowManager {
+    fn get_active_window_data(&mut self) -> Result<ActiveWindowData> {
+        match self {
+            LinuxWindowManager::X11(manager) => manager.get_active_window_data(),
+            LinuxWindowManager::Wayland(manager) => manager.get_active_window_data(),
+        }
+    }
+
+    fn get_idle_time(&mut self) -> Result<u32> {
+        match self {
+            LinuxWindowManager::X11(manager) => manager.get_idle_time(),
+            LinuxWindowManager::Wayland(manager) => manager.get_idle_time(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_latin1_maps_high_bytes_to_matching_unicode_scalars() {
+        // Latin-1 bytes for "café": each byte is its own Unicode scalar value, so 0xE9 becomes
+        // U+00E9 ('é') directly instead of being interpreted (and failing) as UTF-8.
+        let bytes = [0x63, 0x61, 0x66, 0xE9];
+        assert_eq!(decode_latin1(&bytes), "café");
+    }
+
+    #[test]
+    fn decode_compound_text_lossy_drops_iso2022_escape_bytes() {
+        // ESC (0x1b) starts an ISO-2022 charset-designator sequence; we don't implement the state
+        // machine, just drop the control byte itself so it can't corrupt the title.
+        let bytes = b"hello\x1bworld";
+        let decoded = decode_compound_text_lossy(bytes);
+        assert!(!decoded.contains('\u{1b}'));
+        assert_eq!(decoded, "helloworld");
+    }
+
+    #[test]
+    fn decode_compound_text_lossy_preserves_newlines_and_tabs() {
+        let bytes = b"line1\nline2\ttabbed";
+        assert_eq!(decode_compound_text_lossy(bytes), "line1\nline2\ttabbed");
+    }
+}